@@ -20,6 +20,11 @@ impl FractalMath<Mem> for Czech {
         m.square();
         m.plus(origin_re, origin_im);
     }
+
+    // two quadratic steps
+    fn degree(&self) -> f64 {
+        4.0
+    }
 }
 
 fn main() {
@@ -1,9 +1,12 @@
 use rusty_fractals::config::EulerImage;
+use rusty_fractals::euler::EULER_CHANNELS;
 use rusty_fractals::fractal::FractalMath;
 use rusty_fractals::fractal::OrbitType::Infinite;
 use rusty_fractals::mem::Mem;
+use rusty_fractals::metadata::Metadata;
 use rusty_fractals::resolution_multiplier::ResolutionMultiplier::Single;
-use rusty_fractals::{application, mathematician};
+use rusty_fractals::{application, mathematician, rerender};
+use std::env;
 
 /** Fractal Euler type uses three color spectra for better mathematical analysis and better coloring results.
  *  Possible use as:
@@ -22,9 +25,27 @@ impl FractalMath<Mem> for Euler {
         m.square();
         m.plus(origin_re, origin_im);
     }
+
+    // two quadratic steps
+    fn degree(&self) -> f64 {
+        4.0
+    }
 }
 
+/**
+ * cargo run
+ * cargo run -- euler.png width=1600    render the saved image again, with the saved spectral channels
+ */
 fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let euler = |m: &Metadata| {
+        mathematician::init_primes(m.iteration_max);
+        Ok(Euler {})
+    };
+    if rerender::execute_from_args(&args, "Euler", None, euler) {
+        return;
+    }
+
     let fractal_config = EulerImage {
         name: "Euler",
 
@@ -41,6 +62,9 @@ fn main() {
 
         // calculation config
         orbits: Infinite,
+
+        // prime, Fibonacci and other path elements
+        spectral_channels: EULER_CHANNELS,
     };
 
     mathematician::init_primes(fractal_config.iteration_max);
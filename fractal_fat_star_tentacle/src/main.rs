@@ -15,6 +15,11 @@ impl FractalMath<Mem> for FatStarTentacle {
         m.square();
         m.plus(origin_re, origin_im);
     }
+
+    // two quadratic steps
+    fn degree(&self) -> f64 {
+        4.0
+    }
 }
 
 fn main() {
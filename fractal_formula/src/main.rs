@@ -0,0 +1,80 @@
+use rusty_fractals::application;
+use rusty_fractals::config::NebulaImage;
+use rusty_fractals::formula::Formula;
+use rusty_fractals::fractal::OrbitType::Finite;
+use rusty_fractals::metadata::Metadata;
+use rusty_fractals::palettes::PaletteName::BlueToWhiteCircleUp;
+use rusty_fractals::rerender;
+use rusty_fractals::resolution_multiplier::ResolutionMultiplier::Square3;
+use std::env;
+
+/**
+ * Fractal defined by formula file
+ * cargo run -- formula.txt
+ * cargo run -- formula.png width=3840    render the saved image again, by the saved formula
+ */
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if rerender::execute_from_args(&args, "Formula", None, saved_formula) {
+        return;
+    }
+
+    let path = args.first().cloned().unwrap_or_else(|| "formula.txt".to_string());
+
+    let fractal = match Formula::from_file(&path) {
+        Ok(fractal) => fractal,
+        Err(e) => {
+            println!("{}", e);
+            return;
+        }
+    };
+
+    let fractal_config = NebulaImage {
+        name: "Formula",
+
+        iteration_min: 42,
+        iteration_max: 8000,
+        resolution_multiplier: Square3,
+        palette: BlueToWhiteCircleUp,
+
+        width_x: 1000,
+        height_y: 1000,
+        width_re: 3.5,
+        center_re: -0.5,
+        center_im: 0.0,
+
+        orbits: Finite,
+    };
+
+    fractal.init_sequences(fractal_config.iteration_max);
+    application::execute(fractal_config.init(), fractal);
+}
+
+fn saved_formula(m: &Metadata) -> Result<Formula, String> {
+    let fractal: Formula = m
+        .fractal_definition
+        .as_deref()
+        .ok_or("formula missing in metadata")?
+        .parse()?;
+    fractal.init_sequences(m.iteration_max);
+    Ok(fractal)
+}
+
+#[cfg(test)]
+mod tests {
+    use rusty_fractals::formula::Formula;
+    use rusty_fractals::fractal::{FractalMath, MemType};
+    use rusty_fractals::mem::Mem;
+
+    #[test]
+    fn test_math() {
+        let fractal = Formula::from_file("formula.txt").unwrap();
+        fractal.init_sequences(2);
+        let mut m = Mem::new(0.0, 0.0);
+
+        fractal.math(&mut m, 1.0, 0.0);
+        assert_eq!(m.re, 2.0);
+        assert_eq!(m.im, 0.0);
+        assert_eq!(m.it, 1);
+    }
+}
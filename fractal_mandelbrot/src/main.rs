@@ -3,6 +3,8 @@ use rusty_fractals::config::MandelbrotImage;
 use rusty_fractals::fractal::FractalMath;
 use rusty_fractals::mem::Mem;
 use rusty_fractals::palettes::PaletteName::{BlueToWhiteCircleUp, LinearGold};
+use rusty_fractals::rerender;
+use std::env;
 
 /**
  * The Mandelbrot Fractal
@@ -20,7 +22,16 @@ impl FractalMath<Mem> for Mandelbrot {
     }
 }
 
+/**
+ * cargo run
+ * cargo run -- mandelbrot_0.png width=3840 multiplier=Square3    render the saved image again
+ */
 fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if rerender::execute_from_args(&args, "Mandelbrot", None, |_| Ok(Mandelbrot {})) {
+        return;
+    }
+
     let fractal_config = MandelbrotImage {
         name: "Mandelbrot",
 
@@ -26,6 +26,11 @@ impl FractalMath<Mem> for MandelbrotOfMandelbrot {
             + oi
             - i;
     }
+
+    // two quadratic steps
+    fn degree(&self) -> f64 {
+        4.0
+    }
 }
 
 fn main() {
@@ -4,7 +4,9 @@ use rusty_fractals::fractal::OrbitType::Finite;
 use rusty_fractals::fractal::{FractalMath, Optimizer};
 use rusty_fractals::mem::Mem;
 use rusty_fractals::palettes::PaletteName::BlackToWhiteCircleUp;
+use rusty_fractals::rerender;
 use rusty_fractals::resolution_multiplier::ResolutionMultiplier::Square9;
+use std::env;
 
 pub struct Nebula {}
 
@@ -15,7 +17,17 @@ impl FractalMath<Mem> for Nebula {
     }
 }
 
+/**
+ * cargo run
+ * cargo run -- nebula.png width=3840 palette=LinearBlue    render the saved image again
+ */
 fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let o = Some(Optimizer::nebula_optimization());
+    if rerender::execute_from_args(&args, "Nebula", o, |_| Ok(Nebula {})) {
+        return;
+    }
+
     let fractal_config = NebulaImage {
         name: "Nebula",
 
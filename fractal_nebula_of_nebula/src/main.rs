@@ -28,6 +28,11 @@ impl FractalMath<Mem> for NebulaOfNebula {
             + oi
             - i;
     }
+
+    // two quadratic steps
+    fn degree(&self) -> f64 {
+        4.0
+    }
 }
 
 fn main() {
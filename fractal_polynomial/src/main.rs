@@ -0,0 +1,73 @@
+use rusty_fractals::application;
+use rusty_fractals::config::NebulaImage;
+use rusty_fractals::fractal::OrbitType::Finite;
+use rusty_fractals::metadata::Metadata;
+use rusty_fractals::palettes::PaletteName::BlueToWhiteCircleUp;
+use rusty_fractals::polynomial::PolynomialFractal;
+use rusty_fractals::rerender;
+use rusty_fractals::resolution_multiplier::ResolutionMultiplier::Square3;
+use std::env;
+
+/**
+ * Polynomial fractal defined by a config file
+ * cargo run -- polynomial.txt
+ * cargo run -- polynomial.png width=3840    render the saved image again, by the saved polynomials
+ */
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let saved = |m: &Metadata| {
+        m.fractal_definition
+            .as_deref()
+            .ok_or("polynomials missing in metadata")?
+            .parse::<PolynomialFractal>()
+    };
+    if rerender::execute_from_args(&args, "Polynomial", None, saved) {
+        return;
+    }
+
+    let path = args.first().cloned().unwrap_or_else(|| "polynomial.txt".to_string());
+
+    let fractal = match PolynomialFractal::from_file(&path) {
+        Ok(fractal) => fractal,
+        Err(e) => {
+            println!("{}", e);
+            return;
+        }
+    };
+
+    let fractal_config = NebulaImage {
+        name: "Polynomial",
+
+        iteration_min: 42,
+        iteration_max: 8000,
+        resolution_multiplier: Square3,
+        palette: BlueToWhiteCircleUp,
+
+        width_x: 1000,
+        height_y: 1000,
+        width_re: 3.0,
+        center_re: 0.0,
+        center_im: 0.0,
+
+        orbits: Finite,
+    };
+
+    application::execute(fractal_config.init(), fractal);
+}
+
+#[cfg(test)]
+mod tests {
+    use rusty_fractals::fractal::{FractalMath, MemType};
+    use rusty_fractals::mem::Mem;
+    use rusty_fractals::polynomial::PolynomialFractal;
+
+    #[test]
+    fn test_math() {
+        let fractal = PolynomialFractal::from_file("polynomial.txt").unwrap();
+        let mut m = Mem::new(0.0, 0.0);
+
+        fractal.math(&mut m, 1.0, 0.0);
+        assert_eq!(m.re, 2.0);
+        assert_eq!(m.im, 0.0);
+    }
+}
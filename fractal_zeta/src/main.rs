@@ -0,0 +1,60 @@
+use rusty_fractals::application;
+use rusty_fractals::config::NebulaImage;
+use rusty_fractals::fractal::FractalMath;
+use rusty_fractals::fractal::OrbitType::Finite;
+use rusty_fractals::mem_zeta::MemZeta;
+use rusty_fractals::palettes::PaletteName::BlueToWhiteCircleUp;
+use rusty_fractals::resolution_multiplier::ResolutionMultiplier::Square3;
+
+/**
+ * Riemann Zeta fine fractal
+ * z := ζ(z) + c
+ */
+pub struct Zeta {}
+
+impl FractalMath<MemZeta> for Zeta {
+    fn math(&self, mz: &mut MemZeta, origin_re: f64, origin_im: f64) {
+        mz.zeta();
+        mz.plus(origin_re, origin_im);
+    }
+}
+
+fn main() {
+    let fractal_config = NebulaImage {
+        name: "Zeta",
+
+        iteration_min: 12,
+        iteration_max: 800,
+        resolution_multiplier: Square3,
+        palette: BlueToWhiteCircleUp,
+
+        // ζ(-1) = -1/12
+        width_x: 1000,
+        height_y: 1000,
+        width_re: 4.0,
+        center_re: -1.0 / 12.0,
+        center_im: 0.0,
+
+        orbits: Finite,
+    };
+
+    application::execute(fractal_config.init(), Zeta {});
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Zeta;
+    use rusty_fractals::fractal::{FractalMath, MemType};
+    use rusty_fractals::mem_zeta::MemZeta;
+
+    #[test]
+    fn test_math() {
+        let zeta = Zeta {};
+        let mut mz = MemZeta::new(-1.0, 0.0);
+
+        // ζ(-1) + 1/12 = 0
+        zeta.math(&mut mz, 1.0 / 12.0, 0.0);
+        assert!(mz.m.re.abs() < 1e-10);
+        assert!(mz.m.im.abs() < 1e-10);
+    }
+}
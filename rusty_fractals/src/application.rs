@@ -3,6 +3,7 @@ use crate::fractal::{FractalConfig, FractalMath, MemType, Optimizer};
 use crate::machine;
 use crate::machine::Machine;
 use crate::pixel_states::{is_active_new, DomainElementState};
+use crate::tiles;
 use fltk::app::{event_button, event_coords, event_key};
 use fltk::enums::{Color, Event, Key};
 use fltk::window::DoubleWindow;
@@ -38,11 +39,15 @@ fn init_o<'lt, F, M>(
     config: &FractalConfig,
     fractal: F,
     oo: Option<Optimizer>,
-) -> Arc<RwLock<Application<F, M>>>
+) -> Result<Arc<RwLock<Application<F, M>>>, String>
 where
     F: FractalMath<M> + 'static,
     M: MemType<M> + 'static,
 {
+    // palette files are loaded before the window opens
+    let machine = machine::init_o(config, fractal, oo)?;
+    let machine_arc = Arc::new(RwLock::new(machine));
+
     let mut window = Window::default();
     let name = config.name;
 
@@ -52,9 +57,6 @@ where
     window.end();
     window.show();
 
-    let machine = machine::init_o(&config, fractal, oo);
-    let machine_arc = Arc::new(RwLock::new(machine));
-
     let application = Application {
         window: Arc::new(RwLock::new(window)),
         application_data: Arc::new(RwLock::new(ApplicationData { last_max_value: 0 })),
@@ -73,7 +75,7 @@ where
         .unwrap()
         .set_application_ref(application_arc.clone());
 
-    application_arc
+    Ok(application_arc)
 }
 
 pub fn execute<F, M>(config: FractalConfig, fractal: F)
@@ -93,8 +95,23 @@ where
 {
     println!("application.execute()");
 
+    // tiled images are larger than the screen, calculated without window
+    if config.tiling.is_on() {
+        match tiles::execute(&config, fractal) {
+            Ok(path) => println!("saved {}", path),
+            Err(e) => println!("tiles::execute() failed: {}", e),
+        }
+        return;
+    }
+
     let app = app::App::default();
-    let application_arc = init_o(&config, fractal, oo);
+    let application_arc = match init_o(&config, fractal, oo) {
+        Ok(application_arc) => application_arc,
+        Err(e) => {
+            println!("application.execute() failed: {}", e);
+            return;
+        }
+    };
 
     // Window actions
     application_arc.read().unwrap().init_window_actions();
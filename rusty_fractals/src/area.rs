@@ -58,6 +58,10 @@ impl<'lt> Area {
         self.data.read().unwrap().center_im
     }
 
+    pub fn width_re(&self) -> f64 {
+        self.data.read().unwrap().width_re
+    }
+
     pub fn contains(&self, re: f64, im: f64) -> bool {
         let d = self.data.read().unwrap();
         re > d.border_low_re
@@ -0,0 +1,233 @@
+use image::Rgb;
+use std::f64::consts::PI;
+
+/**
+ * Color space in which palette colors are interpolated
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ColorSpace {
+    // steps in sRGB channel values
+    Srgb,
+    // steps in linear light
+    LinearRgb,
+    // perceptually uniform lightness and chroma
+    OkLab,
+    // OKLab in polar coordinates, hue goes around the color wheel
+    OkLch(HueDirection),
+}
+
+/**
+ * Which way to go around the color wheel in OKLCh
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum HueDirection {
+    Shorter,
+    Longer,
+    Increasing,
+    Decreasing,
+}
+
+/**
+ * Color between from (v=0) and to (v=1)
+ */
+pub fn interpolate(from: Rgb<u8>, to: Rgb<u8>, v: f64, space: ColorSpace) -> Rgb<u8> {
+    match space {
+        ColorSpace::Srgb => {
+            let f = to_f64(from);
+            let t = to_f64(to);
+            from_f64(lerp3(f, t, v))
+        }
+        ColorSpace::LinearRgb => {
+            let f = srgb_to_linear(from);
+            let t = srgb_to_linear(to);
+            linear_to_srgb(lerp3(f, t, v))
+        }
+        ColorSpace::OkLab => {
+            let f = linear_to_oklab(srgb_to_linear(from));
+            let t = linear_to_oklab(srgb_to_linear(to));
+            linear_to_srgb(oklab_to_linear(lerp3(f, t, v)))
+        }
+        ColorSpace::OkLch(direction) => {
+            let f = oklab_to_oklch(linear_to_oklab(srgb_to_linear(from)));
+            let t = oklab_to_oklch(linear_to_oklab(srgb_to_linear(to)));
+            let lch = [
+                lerp(f[0], t[0], v),
+                lerp(f[1], t[1], v),
+                interpolate_hue(f, t, v, direction),
+            ];
+            linear_to_srgb(oklab_to_linear(oklch_to_oklab(lch)))
+        }
+    }
+}
+
+fn lerp(from: f64, to: f64, v: f64) -> f64 {
+    from + (to - from) * v
+}
+
+fn lerp3(from: [f64; 3], to: [f64; 3], v: f64) -> [f64; 3] {
+    [
+        lerp(from[0], to[0], v),
+        lerp(from[1], to[1], v),
+        lerp(from[2], to[2], v),
+    ]
+}
+
+fn interpolate_hue(from: [f64; 3], to: [f64; 3], v: f64, direction: HueDirection) -> f64 {
+    // hue of gray is undefined, use the hue of the other color
+    let (h_from, h_to) = match (is_gray(from), is_gray(to)) {
+        (true, true) => return 0.0,
+        (true, false) => (to[2], to[2]),
+        (false, true) => (from[2], from[2]),
+        (false, false) => (from[2], to[2]),
+    };
+    let mut d = (h_to - h_from).rem_euclid(2.0 * PI);
+    match direction {
+        HueDirection::Increasing => {}
+        // equal hues aren't rotated, same as CSS
+        HueDirection::Decreasing if d > 0.0 => d -= 2.0 * PI,
+        HueDirection::Shorter if d > PI => d -= 2.0 * PI,
+        HueDirection::Longer if d < PI => d -= 2.0 * PI,
+        _ => {}
+    }
+    h_from + d * v
+}
+
+fn is_gray(lch: [f64; 3]) -> bool {
+    lch[1] < 1e-4
+}
+
+fn to_f64(c: Rgb<u8>) -> [f64; 3] {
+    [c[0] as f64 / 255.0, c[1] as f64 / 255.0, c[2] as f64 / 255.0]
+}
+
+fn from_f64(c: [f64; 3]) -> Rgb<u8> {
+    let channel = |v: f64| (v.clamp(0.0, 1.0) * 255.0).round() as u8;
+    Rgb([channel(c[0]), channel(c[1]), channel(c[2])])
+}
+
+pub fn srgb_to_linear(c: Rgb<u8>) -> [f64; 3] {
+    to_f64(c).map(|v| {
+        if v <= 0.04045 {
+            v / 12.92
+        } else {
+            ((v + 0.055) / 1.055).powf(2.4)
+        }
+    })
+}
+
+pub fn linear_to_srgb(c: [f64; 3]) -> Rgb<u8> {
+    from_f64(c.map(|v| {
+        if v <= 0.003_130_8 {
+            v * 12.92
+        } else {
+            1.055 * v.powf(1.0 / 2.4) - 0.055
+        }
+    }))
+}
+
+/**
+ * https://bottosson.github.io/posts/oklab/
+ */
+pub fn linear_to_oklab(c: [f64; 3]) -> [f64; 3] {
+    let [r, g, b] = c;
+    let l = (0.412_221_470_8 * r + 0.536_332_536_3 * g + 0.051_445_992_9 * b).cbrt();
+    let m = (0.211_903_498_2 * r + 0.680_699_545_1 * g + 0.107_396_956_6 * b).cbrt();
+    let s = (0.088_302_461_9 * r + 0.281_718_837_6 * g + 0.629_978_700_5 * b).cbrt();
+    [
+        0.210_454_255_3 * l + 0.793_617_785_0 * m - 0.004_072_046_8 * s,
+        1.977_998_495_1 * l - 2.428_592_205_0 * m + 0.450_593_709_9 * s,
+        0.025_904_037_1 * l + 0.782_771_766_2 * m - 0.808_675_766_0 * s,
+    ]
+}
+
+pub fn oklab_to_linear(c: [f64; 3]) -> [f64; 3] {
+    let [l, a, b] = c;
+    let l_ = (l + 0.396_337_777_4 * a + 0.215_803_757_3 * b).powi(3);
+    let m_ = (l - 0.105_561_345_8 * a - 0.063_854_172_8 * b).powi(3);
+    let s_ = (l - 0.089_484_177_5 * a - 1.291_485_548_0 * b).powi(3);
+    [
+        4.076_741_662_1 * l_ - 3.307_711_591_3 * m_ + 0.230_969_929_2 * s_,
+        -1.268_438_004_6 * l_ + 2.609_757_401_1 * m_ - 0.341_319_396_5 * s_,
+        -0.004_196_086_3 * l_ - 0.703_418_614_7 * m_ + 1.707_614_701_0 * s_,
+    ]
+}
+
+fn oklab_to_oklch(c: [f64; 3]) -> [f64; 3] {
+    [c[0], c[1].hypot(c[2]), c[2].atan2(c[1])]
+}
+
+fn oklch_to_oklab(c: [f64; 3]) -> [f64; 3] {
+    [c[0], c[1] * c[2].cos(), c[1] * c[2].sin()]
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::color_space::ColorSpace::{LinearRgb, OkLab, OkLch, Srgb};
+    use crate::color_space::HueDirection::{Decreasing, Increasing, Longer, Shorter};
+    use crate::color_space::{interpolate, linear_to_oklab, linear_to_srgb, oklab_to_linear, srgb_to_linear};
+    use image::Rgb;
+
+    const BLACK: Rgb<u8> = Rgb([0, 0, 0]);
+    const WHITE: Rgb<u8> = Rgb([255, 255, 255]);
+    const RED: Rgb<u8> = Rgb([255, 0, 0]);
+    const BLUE: Rgb<u8> = Rgb([0, 0, 255]);
+
+    #[test]
+    fn test_srgb_linear_round_trip() {
+        for v in [0, 1, 10, 100, 188, 254, 255] {
+            let c = Rgb([v, 255 - v, v / 2]);
+            assert_eq!(linear_to_srgb(srgb_to_linear(c)), c);
+        }
+    }
+
+    #[test]
+    fn test_oklab() {
+        let white = linear_to_oklab(srgb_to_linear(WHITE));
+        assert!((white[0] - 1.0).abs() < 1e-4);
+        assert!(white[1].abs() < 1e-4 && white[2].abs() < 1e-4);
+
+        let c = Rgb([20, 130, 240]);
+        assert_eq!(linear_to_srgb(oklab_to_linear(linear_to_oklab(srgb_to_linear(c)))), c);
+    }
+
+    #[test]
+    fn test_interpolate_end_points() {
+        for space in [Srgb, LinearRgb, OkLab, OkLch(Shorter)] {
+            assert_eq!(interpolate(RED, BLUE, 0.0, space), RED);
+            assert_eq!(interpolate(RED, BLUE, 1.0, space), BLUE);
+        }
+    }
+
+    #[test]
+    fn test_interpolate_gray() {
+        assert_eq!(interpolate(BLACK, WHITE, 0.5, Srgb), Rgb([128, 128, 128]));
+        assert_eq!(interpolate(BLACK, WHITE, 0.5, LinearRgb), Rgb([188, 188, 188]));
+        // lightness 0.5 is linear 0.125
+        assert_eq!(interpolate(BLACK, WHITE, 0.5, OkLab), Rgb([99, 99, 99]));
+        assert_eq!(interpolate(BLACK, WHITE, 0.5, OkLch(Shorter)), Rgb([99, 99, 99]));
+    }
+
+    #[test]
+    fn test_interpolate_hue_direction() {
+        // red to blue, the shorter way goes through purple, the longer through green
+        let shorter = interpolate(RED, BLUE, 0.5, OkLch(Shorter));
+        let longer = interpolate(RED, BLUE, 0.5, OkLch(Longer));
+        assert!(shorter[1] < shorter[0] && shorter[1] < shorter[2]);
+        assert!(longer[1] > longer[0] && longer[1] > longer[2]);
+
+        // from red, the shorter way to blue decreases the hue
+        assert_eq!(interpolate(RED, BLUE, 0.5, OkLch(Decreasing)), shorter);
+        assert_eq!(interpolate(RED, BLUE, 0.5, OkLch(Increasing)), longer);
+    }
+
+    #[test]
+    fn test_interpolate_hue_gray_end_point() {
+        // black has no hue, black to blue stays blue in any direction
+        let shorter = interpolate(BLACK, BLUE, 0.5, OkLch(Shorter));
+        assert!(shorter[2] > shorter[0] && shorter[2] > shorter[1]);
+        for direction in [Decreasing, Increasing] {
+            assert_eq!(interpolate(BLACK, BLUE, 0.5, OkLch(direction)), shorter);
+            assert_eq!(interpolate(BLUE, WHITE, 0.3, OkLch(direction)), interpolate(BLUE, WHITE, 0.3, OkLch(Shorter)));
+        }
+    }
+}
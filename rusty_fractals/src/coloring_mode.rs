@@ -0,0 +1,106 @@
+/**
+ * How to map calculated pixel values to palette colors
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ColoringMode {
+    // the same amount of pixels for each palette color
+    Perfect,
+    // normalized iteration count, interpolated between palette colors
+    // for Mandelbrot like fractals
+    Smooth,
+    // palette repeats every period of iterations
+    // for Mandelbrot like fractals
+    Cyclic(Cycle),
+    // value relative to the highest value
+    Linear,
+    // logarithm of value, relative to logarithm of the highest value
+    Log,
+    // relative value to the power of gamma
+    Power(f64),
+    // inverse hyperbolic sine, linear for low values, logarithmic for high values
+    Asinh,
+}
+
+/**
+ * Repeating palette
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Cycle {
+    // number of iterations for one palette cycle
+    pub period: f64,
+    // phase offset, fraction of period <0, 1)
+    pub offset: f64,
+    // go through palette forward and back, without the jump at the end of cycle
+    pub mirror: bool,
+}
+
+impl ColoringMode {
+    /**
+     * Calculation should remember normalized iteration count
+     */
+    pub fn uses_smooth_iteration(&self) -> bool {
+        matches!(self, ColoringMode::Smooth | ColoringMode::Cyclic(_))
+    }
+
+    /**
+     * Color by value mapped with a function, not by the order of values
+     */
+    pub fn is_mapped(&self) -> bool {
+        matches!(
+            self,
+            ColoringMode::Linear | ColoringMode::Log | ColoringMode::Power(_) | ColoringMode::Asinh
+        )
+    }
+
+    /**
+     * Cycle period has to be positive, palette positions would be NaN otherwise
+     */
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            ColoringMode::Cyclic(c) if !(c.period.is_finite() && c.period > 0.0 && c.offset.is_finite()) => {
+                Err(format!("invalid cycle period {} or offset {}", c.period, c.offset))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::coloring_mode::ColoringMode::{Asinh, Cyclic, Linear, Log, Perfect, Power, Smooth};
+    use crate::coloring_mode::Cycle;
+
+    #[test]
+    fn test_uses_smooth_iteration() {
+        let cycle = Cycle {
+            period: 10.0,
+            offset: 0.0,
+            mirror: false,
+        };
+        assert!(!Perfect.uses_smooth_iteration());
+        assert!(Smooth.uses_smooth_iteration());
+        assert!(Cyclic(cycle).uses_smooth_iteration());
+        assert!(!Log.uses_smooth_iteration());
+    }
+
+    #[test]
+    fn test_is_mapped() {
+        assert!(!Perfect.is_mapped());
+        assert!(!Smooth.is_mapped());
+        assert!(Linear.is_mapped());
+        assert!(Log.is_mapped());
+        assert!(Power(2.2).is_mapped());
+        assert!(Asinh.is_mapped());
+    }
+
+    #[test]
+    fn test_validate() {
+        let cycle = |period| Cyclic(Cycle { period, offset: 0.0, mirror: false });
+
+        assert!(cycle(10.0).validate().is_ok());
+        assert!(cycle(0.0).validate().is_err());
+        assert!(cycle(-1.0).validate().is_err());
+        assert!(cycle(f64::NAN).validate().is_err());
+        assert!(Smooth.validate().is_ok());
+    }
+}
@@ -1,9 +1,11 @@
+use crate::euler::Channel;
 use crate::fractal::FractalCalculationType::{DynamicSequenceNebula, StaticImageMandelbrot, StaticImageNebula, StaticSequenceMandelbrot, StaticSpectralImageEuler};
 use crate::fractal::OrbitType::Ignore;
-use crate::fractal::{FractalConfig, OrbitType};
+use crate::fractal::{FractalConfig, OrbitType, DEFAULT_CONFIG};
 use crate::palettes::PaletteName;
 use crate::resolution_multiplier::ResolutionMultiplier;
 use crate::resolution_multiplier::ResolutionMultiplier::Single;
+use std::borrow::Cow;
 
 pub struct NebulaImage {
     // fractal config
@@ -85,6 +87,8 @@ pub struct EulerImage {
     pub width_re: f64,
     pub center_re: f64,
     pub center_im: f64,
+    // spectral channels of path elements
+    pub spectral_channels: &'static [Channel],
 }
 
 impl NebulaImage {
@@ -96,8 +100,7 @@ impl NebulaImage {
             iteration_min: self.iteration_min,
             iteration_max: self.iteration_max,
             resolution_multiplier: self.resolution_multiplier,
-            palette: self.palette,
-            palette_zero: PaletteName::Nothing,
+            palette: self.palette.clone(),
             width_xl: self.width_x,
             width_xp: self.width_x + 1, // for x = 1, two borders left and right
             height_yl: self.height_y,
@@ -105,8 +108,7 @@ impl NebulaImage {
             width_re: self.width_re,
             center_re: self.center_re,
             center_im: self.center_im,
-            update_max: 1,
-            update_min: 0,
+            ..DEFAULT_CONFIG
         }
     }
 }
@@ -120,8 +122,7 @@ impl NebulaVideo {
             iteration_min: self.iteration_min,
             iteration_max: self.iteration_max,
             resolution_multiplier: self.resolution_multiplier,
-            palette: self.palette,
-            palette_zero: PaletteName::Nothing,
+            palette: self.palette.clone(),
             width_xl: self.width_x,
             width_xp: self.width_x + 1,
             height_yl: self.height_y,
@@ -131,6 +132,7 @@ impl NebulaVideo {
             center_im: self.center_im,
             update_max: self.update_max,
             update_min: self.update_min,
+            ..DEFAULT_CONFIG
         }
     }
 }
@@ -144,8 +146,8 @@ impl MandelbrotImage {
             iteration_min: 0,
             iteration_max: self.iteration_max,
             resolution_multiplier: Single,
-            palette: self.palette,
-            palette_zero: self.palette_zero,
+            palette: self.palette.clone(),
+            palette_zero: self.palette_zero.clone(),
             width_xl: self.width_x,
             width_xp: self.width_x + 1,
             height_yl: self.height_y,
@@ -153,8 +155,7 @@ impl MandelbrotImage {
             width_re: self.width_re,
             center_re: self.center_re,
             center_im: self.center_im,
-            update_max: 1,
-            update_min: 0,
+            ..DEFAULT_CONFIG
         }
     }
 }
@@ -168,8 +169,8 @@ impl MandelbrotVideo {
             iteration_min: 0,
             iteration_max: self.iteration_max,
             resolution_multiplier: Single,
-            palette: self.palette,
-            palette_zero: self.palette_zero,
+            palette: self.palette.clone(),
+            palette_zero: self.palette_zero.clone(),
             width_xl: self.width_x,
             width_xp: self.width_x + 1,
             height_yl: self.height_y,
@@ -177,8 +178,7 @@ impl MandelbrotVideo {
             width_re: self.width_re,
             center_re: self.center_re,
             center_im: self.center_im,
-            update_max: 1,
-            update_min: 0,
+            ..DEFAULT_CONFIG
         }
     }
 }
@@ -193,7 +193,6 @@ impl EulerImage {
             iteration_max: self.iteration_max,
             resolution_multiplier: self.resolution_multiplier,
             palette: PaletteName::Nothing,
-            palette_zero: PaletteName::Nothing,
             width_xl: self.width_x,
             width_xp: self.width_x + 1,
             height_yl: self.height_y,
@@ -201,8 +200,8 @@ impl EulerImage {
             width_re: self.width_re,
             center_re: self.center_re,
             center_im: self.center_im,
-            update_max: 1,
-            update_min: 0,
+            spectral_channels: Cow::Borrowed(self.spectral_channels),
+            ..DEFAULT_CONFIG
         }
     }
 }
@@ -4,9 +4,6 @@ use image::Rgb;
 // If there is nothing interesting going on around the specific element, the (new) element will be ignored.
 pub const NEIGHBOURS: u8 = 3;
 
-// save generated images here
-pub const PATH: &str = "/Fractals/";
-
 // to remove noise, ignore pixels with this value or less as 0
 pub const COLORING_THRESHOLD: u64 = 3;
 
@@ -0,0 +1,110 @@
+// The method used for cyclic coloring is
+// - Palette repeats every period of iterations, shifted by phase offset
+// - Escaped pixels use normalized iteration count, so there are no color bands
+// - Mandelbrot set insides color by zero palette, one cycle over quad <0, 4>
+// - Mirror goes through the palette forward and then back
+
+use crate::coloring_mode::Cycle;
+use crate::constants::CALCULATION_BOUNDARY;
+use crate::data_image::DataImage;
+use crate::palette::Palette;
+
+pub fn cyclically_color_mandelbrot_values(
+    data: &DataImage,
+    palette: &Palette,
+    palette_zero: &Palette,
+    cycle: Cycle,
+) {
+    println!("cyclically_color_mandelbrot_values()");
+
+    for y in 0..data.height_yp {
+        for x in 0..data.width_xp {
+            let (value, quad, smooth) = data.value_quad_smooth_at(x, y);
+            if value == 0 {
+                // Paint insides of Mandelbrot set
+                if !palette_zero.spectrum.is_empty() {
+                    let t = phase(quad / CALCULATION_BOUNDARY as f64, cycle);
+                    data.color(x, y, palette_zero.spectrum_value_at_fraction(t));
+                }
+            } else {
+                // smooth value isn't available for the pixel which escaped immediately
+                let v = smooth.unwrap_or(value as f64);
+                let t = phase(v / cycle.period, cycle);
+                data.color(x, y, palette.spectrum_value_at_fraction(t));
+            }
+        }
+    }
+}
+
+/**
+ * Position in one palette cycle <0, 1>
+ * v is the number of periods
+ */
+pub fn phase(v: f64, cycle: Cycle) -> f64 {
+    let t = (v + cycle.offset).rem_euclid(1.0);
+    if cycle.mirror {
+        1.0 - (2.0 * t - 1.0).abs()
+    } else {
+        t
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::area;
+    use crate::coloring_mode::Cycle;
+    use crate::cyclic_color_distribution::{cyclically_color_mandelbrot_values, phase};
+    use crate::data_image::init;
+    use crate::fractal::init_trivial_dynamic_config;
+    use crate::palette::init_trivial;
+    use crate::pixel_states::DomainElementState::FinishedSuccess;
+    use image::Rgb;
+
+    fn cycle(offset: f64, mirror: bool) -> Cycle {
+        Cycle {
+            period: 4.0,
+            offset,
+            mirror,
+        }
+    }
+
+    #[test]
+    fn test_phase() {
+        assert_eq!(phase(0.25, cycle(0.0, false)), 0.25);
+        assert_eq!(phase(1.25, cycle(0.0, false)), 0.25);
+        assert_eq!(phase(0.75, cycle(0.5, false)), 0.25);
+        assert_eq!(phase(-0.25, cycle(0.0, false)), 0.75);
+    }
+
+    #[test]
+    fn test_phase_mirror() {
+        assert_eq!(phase(0.25, cycle(0.0, true)), 0.5);
+        assert_eq!(phase(0.5, cycle(0.0, true)), 1.0);
+        assert_eq!(phase(0.75, cycle(0.0, true)), 0.5);
+    }
+
+    #[test]
+    fn test_cyclically_color_mandelbrot_values() {
+        let c = init_trivial_dynamic_config(3);
+        let a = area::init(&c);
+        let di = init(&c, &a);
+        let p = init_trivial();
+
+        // escaped after 1, 2 and 5 iterations, period 4
+        di.set_pixel_mandelbrot(0, 0, 1, 5.0, FinishedSuccess, 10);
+        di.set_pixel_mandelbrot(1, 0, 2, 5.0, FinishedSuccess, 10);
+        di.set_pixel_mandelbrot(2, 0, 5, 5.0, FinishedSuccess, 10);
+        // inside, quad 2.0 is half of the cycle
+        di.set_pixel_mandelbrot(0, 1, 10, 2.0, FinishedSuccess, 10);
+        // smooth value below zero is still a smooth value
+        di.set_pixel_mandelbrot(1, 1, 1, 5.0, FinishedSuccess, 10);
+        di.set_pixel_smooth(1, 1, -1.0);
+        cyclically_color_mandelbrot_values(&di, &p, &p, cycle(0.0, false));
+
+        assert_eq!(di.color_at(0, 0), Some(Rgb([128, 128, 0])));
+        assert_eq!(di.color_at(1, 0), Some(Rgb([0, 255, 0])));
+        assert_eq!(di.color_at(2, 0), Some(Rgb([128, 128, 0])));
+        assert_eq!(di.color_at(0, 1), Some(Rgb([0, 255, 0])));
+        assert_eq!(di.color_at(1, 1), Some(Rgb([0, 128, 128])));
+    }
+}
@@ -1,10 +1,12 @@
 use crate::area::Area;
 use crate::constants::{MINIMUM_PATH_LENGTH, NEIGHBOURS};
 use crate::data_px;
-use crate::data_px::DataPx;
+use crate::data_px::{DataPx, PxValues};
+use crate::data_px3;
 use crate::data_px3::DataPx3;
+use crate::euler::{classify, mix_channels, Channel};
+use crate::fractal::FractalCalculationType::StaticSpectralImageEuler;
 use crate::fractal::{FractalConfig, Optimizer};
-use crate::pixel::Spectra::{Blue, Green, Red};
 use crate::pixel_states::DomainElementState::{
     ActiveNew, FinishedSuccess, FinishedSuccessPast, FinishedTooLong, FinishedTooShort,
     HibernatedDeepBlack,
@@ -16,6 +18,7 @@ use crate::pixel_states::{
 use crate::resolution_multiplier::ResolutionMultiplier;
 use crate::resolution_multiplier::ResolutionMultiplier::Square2;
 use image::Rgb;
+use std::borrow::Cow;
 use std::sync::{Arc, RwLock};
 use ResolutionMultiplier::{Single, Square101, Square11, Square3, Square5, Square51, Square9};
 
@@ -31,6 +34,7 @@ pub struct DataImage {
      */
     pub pixels: Vec<DataPx>,
     pub pixels3: Vec<DataPx3>,
+    pub spectral_channels: Cow<'static, [Channel]>,
     /*
      * dynamic data for zoom video
      * As zoom progress, points [re,im] are projected to new pixels [px,py] until they migrate out of the tiny area.
@@ -45,14 +49,8 @@ impl DataImage {
         self.px_at(x, y).set_c(palette_color);
     }
 
-    pub fn color_r(&self, x: usize, y: usize, cr: u8) {
-        self.px_at3(x, y).set_c(Red, cr);
-    }
-    pub fn color_g(&self, x: usize, y: usize, c: u8) {
-        self.px_at3(x, y).set_c(Green, c);
-    }
-    pub fn color_b(&self, x: usize, y: usize, c: u8) {
-        self.px_at3(x, y).set_c(Blue, c);
+    pub fn color_channel(&self, x: usize, y: usize, channel: usize, c: u8) {
+        self.px_at3(x, y).set_c(channel, c);
     }
 
     pub fn translate_one_path_to_point_grid_now(&self, path: Vec<[f64; 2]>, area: &Area) {
@@ -62,6 +60,20 @@ impl DataImage {
         }
     }
 
+    /**
+     * Euler fractal, each path element adds to the first matching spectral channel
+     * iterations are the iteration numbers of the path elements
+     */
+    pub fn translate_one_path_to_spectra_now(&self, path: Vec<[f64; 2]>, iterations: Vec<u64>, area: &Area) {
+        let path_length = path.len() as u64;
+        for (i, ([re, im], iteration)) in path.into_iter().zip(iterations).enumerate() {
+            if let Some(channel) = classify(&self.spectral_channels, i as u64, path_length, iteration) {
+                let (x, y) = area.point_to_pixel(re, im);
+                self.px_at3(x, y).add_v1(channel);
+            }
+        }
+    }
+
     pub fn translate_all_paths_to_point_grid(&self, area: &Area) {
         println!("translate_all_paths_to_point_grid()");
         let all = self.paths.read().unwrap().to_owned();
@@ -166,16 +178,40 @@ impl DataImage {
         self.px_at(x, y).get_vs()
     }
 
+    pub fn value_trap_at(&self, x: usize, y: usize) -> (u64, f64) {
+        self.px_at(x, y).get_vt()
+    }
+
+    pub fn value_quad_smooth_at(&self, x: usize, y: usize) -> (u64, f64, Option<f64>) {
+        self.px_at(x, y).get_vqn()
+    }
+
+    pub fn px_values_at(&self, x: usize, y: usize) -> PxValues {
+        self.px_at(x, y).get_vsqtn()
+    }
+
+    pub fn set_px_values(&self, x: usize, y: usize, values: PxValues) {
+        self.px_at(x, y).set_vsqtn(values);
+    }
+
     pub fn value_at(&self, x: usize, y: usize) -> u64 {
         self.px_at(x, y).get_v()
     }
 
-    pub fn value_at3(&self, x: usize, y: usize) -> (u64, u64, u64) {
-        self.px_at3(x, y).get_v3()
+    pub fn value_at_channel(&self, x: usize, y: usize, channel: usize) -> u64 {
+        self.px_at3(x, y).get_v(channel)
     }
 
+    pub fn values_at3(&self, x: usize, y: usize) -> Vec<u64> {
+        self.px_at3(x, y).get_vs()
+    }
+
+    /**
+     * Mix colors of all spectral channels, as the final color of pixel
+     */
     pub fn define_color_at3(&self, x: usize, y: usize) {
-        self.px_at3(x, y).define_color3()
+        let colors = self.px_at3(x, y).get_cs();
+        self.color(x, y, mix_channels(&self.spectral_channels, &colors));
     }
 
     pub fn state_origin_at(&self, x: usize, y: usize) -> (DomainElementState, f64, f64) {
@@ -207,6 +243,16 @@ impl DataImage {
         self.px_at(x, y).set_qsv(quad, state, value);
     }
 
+    // for Mandelbrot like fractals with orbit trap
+    pub fn set_pixel_trap(&self, x: usize, y: usize, trap: f64) {
+        self.px_at(x, y).set_t(trap);
+    }
+
+    // for Mandelbrot like fractals with smooth coloring
+    pub fn set_pixel_smooth(&self, x: usize, y: usize, smooth: f64) {
+        self.px_at(x, y).set_n(smooth);
+    }
+
     // for Nebula like fractals
     pub fn set_pixel_state(&self, x: usize, y: usize, state: DomainElementState) {
         self.px_at(x, y).set_qs(1.0, state);
@@ -427,11 +473,24 @@ pub fn init_o(conf: &FractalConfig, area: &Area, oo: Option<Optimizer>) -> DataI
         is_dynamic: conf.is_dynamic(),
         is_mandelbrot: conf.is_mandelbrot(),
         pixels: init_domain(area, oo),
-        pixels3: Vec::new(),
+        pixels3: init_domain3(conf, area),
+        spectral_channels: conf.spectral_channels.clone(),
         paths: Arc::new(RwLock::new(Vec::new())),
     }
 }
 
+/**
+ * Spectral channels are used only by Euler fractal
+ */
+fn init_domain3(conf: &FractalConfig, area: &Area) -> Vec<DataPx3> {
+    if conf.fractal_calc_type != StaticSpectralImageEuler {
+        return Vec::new();
+    }
+    (0..area.width_xp() * area.height_yp())
+        .map(|_| data_px3::init(conf.spectral_channels.len()))
+        .collect()
+}
+
 /**
  * [0,0] is at the top left
  */
@@ -488,7 +547,9 @@ fn check_domain(x: i32, y: i32, width: usize, height: usize) -> bool {
 mod tests {
     use crate::area;
     use crate::data_image::{check_domain, color_for_state, init};
+    use crate::fractal::FractalCalculationType::StaticSpectralImageEuler;
     use crate::fractal::{init_trivial_dynamic_config, FractalConfig};
+    use crate::mathematician;
     use crate::pixel_states::DomainElementState::ActiveNew;
     use crate::resolution_multiplier::ResolutionMultiplier::{
         Square101, Square11, Square3, Square5, Square51, Square9,
@@ -501,6 +562,21 @@ mod tests {
     static CONF: FractalConfig = init_trivial_dynamic_config(3);
     static AREA: LazyLock<Area> = LazyLock::new(|| area::init(&CONF));
 
+    #[test]
+    fn test_translate_one_path_to_spectra_now() {
+        let mut conf = init_trivial_dynamic_config(3);
+        conf.fractal_calc_type = StaticSpectralImageEuler;
+        let di = init(&conf, &AREA);
+        mathematician::init_primes(10);
+        mathematician::init_fibonacci(10);
+
+        // element indexes 2, 3 and 5 are prime, 1 Fibonacci, 0 and 4 neither
+        di.translate_one_path_to_spectra_now(vec![[0.0, 0.0]; 6], (1..=6).collect(), &AREA);
+        assert_eq!(di.values_at3(1, 1), vec![3, 1, 2]);
+        assert_eq!(di.values_at3(0, 0), vec![0, 0, 0]);
+        assert_eq!(di.value_at_channel(1, 1, 0), 3);
+    }
+
     #[test]
     fn test_px_at() {
         let di = init(&CONF, &AREA);
@@ -1,4 +1,5 @@
 use crate::data::{DataPxSer, DataSer};
+use crate::orbit_trap::NO_TRAP;
 use crate::pixel_states::DomainElementState;
 use crate::pixel_states::DomainElementState::{
     ActiveNew, FinishedSuccess, FinishedSuccessPast, FinishedTooLong, FinishedTooShort,
@@ -7,12 +8,15 @@ use crate::pixel_states::DomainElementState::{
 use image::Rgb;
 use std::sync::RwLock;
 
+// value, state, quad, trap and smooth value, the calculation result of Mandelbrot pixel
+pub type PxValues = (u64, DomainElementState, f64, f64, Option<f64>);
+
 pub struct DataPx {
     is_alive: RwLock<bool>,
     data: RwLock<Data>,
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone)]
 struct Data {
     origin_re: f64,
     origin_im: f64,
@@ -23,9 +27,34 @@ struct Data {
      */
     state: DomainElementState,
     quad: f64,
+    // only for Mandelbrot pixels with orbit trap or smooth coloring, other pixels don't pay for it
+    mandelbrot: Option<Box<MandelbrotData>>,
     color: Option<Rgb<u8>>,
 }
 
+#[derive(Clone, Copy)]
+struct MandelbrotData {
+    // the closest distance of calculation path to orbit trap
+    trap: f64,
+    // normalized (continuous) iteration count of escaped calculation, None if not calculated
+    smooth: Option<f64>,
+}
+
+impl Data {
+    fn trap(&self) -> f64 {
+        self.mandelbrot.as_ref().map_or(NO_TRAP, |m| m.trap)
+    }
+
+    fn smooth(&self) -> Option<f64> {
+        self.mandelbrot.as_ref().and_then(|m| m.smooth)
+    }
+
+    fn mandelbrot_mut(&mut self) -> &mut MandelbrotData {
+        self.mandelbrot
+            .get_or_insert_with(|| Box::new(MandelbrotData { trap: NO_TRAP, smooth: None }))
+    }
+}
+
 impl DataPx {
     pub fn new(is_alive: bool, data: DataSer) -> Self {
         Self {
@@ -36,6 +65,7 @@ impl DataPx {
                 value: data.value,
                 state: data.state,
                 quad: data.quad,
+                mandelbrot: None,
                 color: None,
             }),
         }
@@ -62,6 +92,22 @@ impl DataPx {
         d.state = state;
     }
 
+    pub fn set_t(&self, trap: f64) {
+        self.data.write().unwrap().mandelbrot_mut().trap = trap;
+    }
+
+    pub fn set_n(&self, smooth: f64) {
+        // not defined for the calculation which escaped immediately
+        self.data.write().unwrap().mandelbrot_mut().smooth = smooth.is_finite().then_some(smooth);
+    }
+
+    pub fn set_vsqtn(&self, values: PxValues) {
+        let (value, state, quad, trap, smooth) = values;
+        let mut d = self.data.write().unwrap();
+        (d.value, d.state, d.quad) = (value, state, quad);
+        d.mandelbrot = (trap != NO_TRAP || smooth.is_some()).then(|| Box::new(MandelbrotData { trap, smooth }));
+    }
+
     pub fn set_c(&self, color: Rgb<u8>) {
         self.data.write().unwrap().color = Some(color);
     }
@@ -71,11 +117,26 @@ impl DataPx {
         (d.value, d.state, d.quad, d.color)
     }
 
+    pub fn get_vsqtn(&self) -> PxValues {
+        let d = self.data.read().unwrap();
+        (d.value, d.state, d.quad, d.trap(), d.smooth())
+    }
+
     pub fn get_vsc(&self) -> (u64, DomainElementState, Option<Rgb<u8>>) {
         let d = self.data.read().unwrap();
         (d.value, d.state, d.color)
     }
 
+    pub fn get_vt(&self) -> (u64, f64) {
+        let d = self.data.read().unwrap();
+        (d.value, d.trap())
+    }
+
+    pub fn get_vqn(&self) -> (u64, f64, Option<f64>) {
+        let d = self.data.read().unwrap();
+        (d.value, d.quad, d.smooth())
+    }
+
     pub fn get_vs(&self) -> (u64, DomainElementState) {
         let d = self.data.read().unwrap();
         (d.value, d.state)
@@ -162,6 +223,7 @@ impl DataPx {
         d.value = 0;
         d.state = state;
         d.quad = 0.0;
+        d.mandelbrot = None;
         d.color = None;
     }
 
@@ -174,6 +236,7 @@ impl DataPx {
         s.value = m.value;
         s.state = m.state;
         s.quad = m.quad;
+        s.mandelbrot = m.mandelbrot.clone();
         s.color = m.color;
 
         // is alive
@@ -210,6 +273,7 @@ pub fn init(origin_re: f64, origin_im: f64, state: DomainElementState) -> DataPx
             value: 0,
             state,
             quad: 0.0,
+            mandelbrot: None,
             color: None,
         }),
     }
@@ -222,6 +286,7 @@ pub fn init_trivial() -> DataPx {
 #[cfg(test)]
 mod tests {
     use crate::data_px::init_trivial;
+    use crate::orbit_trap::NO_TRAP;
     use crate::pixel_states::DomainElementState::{
         ActiveNew, FinishedSuccess, FinishedSuccessPast, FinishedTooLong, FinishedTooShort,
     };
@@ -265,6 +330,40 @@ mod tests {
         assert_eq!(d.state, FinishedTooShort);
     }
 
+    #[test]
+    fn test_set_t() {
+        let p = init_trivial();
+        // nebula and Euler pixels have no Mandelbrot data
+        p.set_qsv(2.2, FinishedSuccess, 8);
+        assert!(p.data.read().unwrap().mandelbrot.is_none());
+
+        p.set_t(0.5);
+        assert_eq!(p.data.read().unwrap().trap(), 0.5);
+        p.reset(0.0, 0.0, ActiveNew);
+        assert!(p.data.read().unwrap().mandelbrot.is_none());
+    }
+
+    #[test]
+    fn test_set_vsqtn() {
+        let p = init_trivial();
+        let values = (7, FinishedSuccess, 2.5, 0.5, Some(7.25));
+
+        p.set_vsqtn(values);
+        assert_eq!(p.get_vsqtn(), values);
+        p.set_vsqtn((7, FinishedSuccess, 2.5, NO_TRAP, None));
+        assert!(p.data.read().unwrap().mandelbrot.is_none());
+    }
+
+    #[test]
+    fn test_set_n() {
+        let p = init_trivial();
+
+        p.set_n(3.25);
+        assert_eq!(p.data.read().unwrap().smooth(), Some(3.25));
+        p.set_n(f64::NAN);
+        assert_eq!(p.data.read().unwrap().smooth(), None);
+    }
+
     #[test]
     fn test_set_c() {
         let p = init_trivial();
@@ -294,6 +393,27 @@ mod tests {
         assert_eq!(rc, None);
     }
 
+    #[test]
+    fn test_get_vt() {
+        let p = init_trivial();
+
+        let (rv, rt) = p.get_vt();
+        assert_eq!(rv, 0);
+        assert_eq!(rt, NO_TRAP);
+    }
+
+    #[test]
+    fn test_get_vqn() {
+        let p = init_trivial();
+
+        p.set_qsv(4.5, FinishedSuccess, 7);
+        p.set_n(7.5);
+        let (rv, rq, rn) = p.get_vqn();
+        assert_eq!(rv, 7);
+        assert_eq!(rq, 4.5);
+        assert_eq!(rn, Some(7.5));
+    }
+
     #[test]
     fn test_get_vs() {
         let p = init_trivial();
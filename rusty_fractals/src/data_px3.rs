@@ -1,52 +1,91 @@
-use crate::pixel::Spectra;
-use crate::pixel_states::DomainElementState;
-use image::Rgb;
 use std::sync::RwLock;
 
+/**
+ * Pixel of Euler fractal, with values and colors of each spectral channel
+ */
 pub struct DataPx3 {
-    _is_alive: RwLock<bool>, // TODO
     data3: RwLock<Data3>,
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Default)]
 struct Data3 {
-    _origin_re: f64, // TODO
-    _origin_im: f64, // TODO
-    value_r: u64,
-    value_g: u64,
-    value_b: u64,
-    // Element state is decided by calculation result.
-    // Alternatively: If all it's neighbours finished too long,
-    // it is going to be created as HibernatedBlack and its origin won't seed any calculation path.
-    _state: DomainElementState, // TODO
-    color_r: u8,
-    color_g: u8,
-    color_b: u8,
-    color: Rgb<u8>,
+    values: Vec<u64>,
+    colors: Vec<u8>,
 }
 
 impl DataPx3 {
-    pub fn get_v3(&self) -> (u64, u64, u64) {
-        let d = self.data3.read().unwrap();
-        (d.value_r, d.value_g, d.value_b)
-    }
-
-    pub fn set_c(&self, sp: Spectra, spectra_color_index: u8) {
-        match sp {
-            Spectra::Red => {
-                self.data3.write().unwrap().color_r = spectra_color_index;
-            }
-            Spectra::Green => {
-                self.data3.write().unwrap().color_g = spectra_color_index;
-            }
-            Spectra::Blue => {
-                self.data3.write().unwrap().color_b = spectra_color_index;
-            }
-        }
-    }
-
-    pub fn define_color3(&self) {
-        let d = self.data3.read().unwrap();
-        self.data3.write().unwrap().color = Rgb([d.color_r, d.color_g, d.color_b]);
+    pub fn add_v1(&self, channel: usize) {
+        self.data3.write().unwrap().values[channel] += 1;
+    }
+
+    pub fn get_v(&self, channel: usize) -> u64 {
+        self.data3.read().unwrap().values[channel]
+    }
+
+    pub fn get_vs(&self) -> Vec<u64> {
+        self.data3.read().unwrap().values.clone()
+    }
+
+    pub fn set_c(&self, channel: usize, spectra_color_index: u8) {
+        self.data3.write().unwrap().colors[channel] = spectra_color_index;
+    }
+
+    /**
+     * Colors of all channels, to be mixed into the final pixel color
+     */
+    pub fn get_cs(&self) -> Vec<u8> {
+        self.data3.read().unwrap().colors.clone()
+    }
+
+    pub fn clear(&self) {
+        let mut d = self.data3.write().unwrap();
+        d.values.fill(0);
+        d.colors.fill(0);
+    }
+}
+
+pub fn init(channels: usize) -> DataPx3 {
+    DataPx3 {
+        data3: RwLock::new(Data3 {
+            values: vec![0; channels],
+            colors: vec![0; channels],
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::data_px3::init;
+
+    #[test]
+    fn test_add_v1() {
+        let p = init(3);
+
+        p.add_v1(0);
+        p.add_v1(2);
+        p.add_v1(2);
+        assert_eq!(p.get_vs(), vec![1, 0, 2]);
+        assert_eq!(p.get_v(2), 2);
+    }
+
+    #[test]
+    fn test_set_c() {
+        let p = init(4);
+
+        p.set_c(0, 10);
+        p.set_c(1, 20);
+        p.set_c(3, 30);
+        assert_eq!(p.get_cs(), vec![10, 20, 0, 30]);
+    }
+
+    #[test]
+    fn test_clear() {
+        let p = init(3);
+
+        p.add_v1(1);
+        p.set_c(1, 20);
+        p.clear();
+        assert_eq!(p.get_vs(), vec![0, 0, 0]);
+        assert_eq!(p.get_cs(), vec![0, 0, 0]);
     }
 }
@@ -1,36 +1,157 @@
-use crate::mathematician;
-use crate::pixel::Spectra;
-use crate::pixel::Spectra::{Blue, Green, Red};
-
-fn _translate_paths_to_pixel_grid() {
-    /*
-    for path in paths {
-        for i in 0..path.size() - 1 {
-            let tmp = path.get(i);
-            // translate [re,im] to [px,py]
-            AreaFinebrot.pointToPixel(m, tmp[0], tmp[1]);
-            if m.good {
-                added += 1;
-                FractalEuler.colorsFor(m, i, path.size());
-                PixelsEulerFinebrot.add(m.px, m.py, m.spectra);
-            }
-        }
+use crate::mathematician::Sequence;
+use crate::mathematician::Sequence::{Fibonacci, Prime};
+use image::Rgb;
+use std::borrow::Cow;
+
+/**
+ * Property of calculation path element tested by classifier
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Subject {
+    // order of element on the recorded path
+    ElementIndex,
+    // number of recorded elements of the whole path
+    PathLength,
+    // iteration at which the element was calculated
+    Iteration,
+}
+
+/**
+ * Path element matches, if its subject is member of the sequence
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Classifier {
+    pub sequence: Sequence,
+    pub subject: Subject,
+}
+
+/**
+ * Spectral channel of Euler type fractal
+ * - element belongs to the channel if any of the classifiers matches
+ * - channel without classifiers takes all remaining elements
+ * - color is a row of the color matrix, weights of red, green and blue in 0.0 to 1.0
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub struct Channel {
+    // borrowed by const channels, owned when read from image metadata
+    pub classifiers: Cow<'static, [Classifier]>,
+    pub color: [f64; 3],
+}
+
+/**
+ * The original Euler channels
+ * - prime element index                            -> Red spectrum
+ * - prime path length or Fibonacci element index   -> Green spectrum
+ * - everything else                                -> Blue spectrum
+ */
+pub const EULER_CHANNELS: &[Channel] = &[
+    Channel {
+        classifiers: Cow::Borrowed(&[Classifier { sequence: Prime, subject: Subject::ElementIndex }]),
+        color: [1.0, 0.0, 0.0],
+    },
+    Channel {
+        classifiers: Cow::Borrowed(&[
+            Classifier { sequence: Prime, subject: Subject::PathLength },
+            Classifier { sequence: Fibonacci, subject: Subject::ElementIndex },
+        ]),
+        color: [0.0, 1.0, 0.0],
+    },
+    Channel {
+        classifiers: Cow::Borrowed(&[]),
+        color: [0.0, 0.0, 1.0],
+    },
+];
+
+impl Classifier {
+    pub fn matches(&self, element_index: u64, path_length: u64, iteration: u64) -> bool {
+        let n = match self.subject {
+            Subject::ElementIndex => element_index,
+            Subject::PathLength => path_length,
+            Subject::Iteration => iteration,
+        };
+        self.sequence.contains(n)
     }
-     */
 }
 
-fn _colors_for(element_index: u64, path_length: u64) -> Spectra {
-    if mathematician::is_prime(element_index) {
-        return Red;
+/**
+ * Initialize all sequences used by the channels, up to max
+ */
+pub fn init_sequences(channels: &[Channel], max: u64) {
+    for channel in channels {
+        for classifier in channel.classifiers.iter() {
+            classifier.sequence.init(max);
+        }
     }
-    if mathematician::is_prime(path_length) {
-        return Green;
+}
+
+/**
+ * Index of the first channel matching the path element
+ * None if no channel matches and there is no channel without classifiers
+ */
+pub fn classify(channels: &[Channel], element_index: u64, path_length: u64, iteration: u64) -> Option<usize> {
+    channels.iter().position(|channel| {
+        channel.classifiers.is_empty()
+            || channel
+                .classifiers
+                .iter()
+                .any(|c| c.matches(element_index, path_length, iteration))
+    })
+}
+
+/**
+ * Final pixel color, intensities of all channels mixed by the color matrix
+ */
+pub fn mix_channels(channels: &[Channel], intensities: &[u8]) -> Rgb<u8> {
+    let mut rgb = [0.0; 3];
+    for (channel, intensity) in channels.iter().zip(intensities) {
+        for (c, weight) in rgb.iter_mut().zip(channel.color) {
+            *c += *intensity as f64 * weight;
+        }
     }
-    Blue
+    Rgb(rgb.map(|c| c.round().clamp(0.0, 255.0) as u8))
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::euler::{classify, init_sequences, mix_channels, Channel, Classifier, Subject, EULER_CHANNELS};
+    use crate::mathematician::Sequence::{Square, Triangular};
+    use image::Rgb;
+    use std::borrow::Cow;
+
+    #[test]
+    fn test_classify() {
+        init_sequences(EULER_CHANNELS, 100);
+
+        assert_eq!(classify(EULER_CHANNELS, 7, 10, 1), Some(0));
+        assert_eq!(classify(EULER_CHANNELS, 10, 11, 1), Some(1));
+        assert_eq!(classify(EULER_CHANNELS, 21, 10, 1), Some(1));
+        assert_eq!(classify(EULER_CHANNELS, 10, 12, 1), Some(2));
+    }
+
     #[test]
-    fn test_it() {}
+    fn test_classify_iteration() {
+        const CHANNELS: &[Channel] = &[Channel {
+            classifiers: Cow::Borrowed(&[Classifier { sequence: Square, subject: Subject::Iteration }]),
+            color: [1.0, 1.0, 1.0],
+        }];
+        init_sequences(CHANNELS, 100);
+
+        assert_eq!(classify(CHANNELS, 3, 3, 16), Some(0));
+        assert_eq!(classify(CHANNELS, 16, 16, 3), None);
+    }
+
+    #[test]
+    fn test_mix_channels() {
+        const CHANNELS: &[Channel] = &[
+            Channel { classifiers: Cow::Borrowed(&[]), color: [1.0, 0.0, 0.0] },
+            Channel { classifiers: Cow::Borrowed(&[]), color: [0.0, 0.5, 0.5] },
+            Channel {
+                classifiers: Cow::Borrowed(&[Classifier { sequence: Triangular, subject: Subject::PathLength }]),
+                color: [1.0, 1.0, 0.0],
+            },
+        ];
+
+        assert_eq!(mix_channels(CHANNELS, &[100, 100, 0]), Rgb([100, 50, 50]));
+        assert_eq!(mix_channels(CHANNELS, &[200, 0, 100]), Rgb([255, 100, 0]));
+    }
 }
@@ -1,20 +1,201 @@
+use crate::color_space::srgb_to_linear;
 use crate::data_image::DataImage;
-use image::{ImageBuffer, RgbImage};
+use crate::mapped_color_distribution::max_and_median;
+use crate::metadata::Metadata;
+use image::codecs::jpeg::JpegEncoder;
+use image::imageops::FilterType;
+use image::{ImageBuffer, ImageFormat, Luma, Rgb, Rgb32FImage, RgbImage};
+use std::fs;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+use tiff::encoder::{colortype, TiffEncoder};
 
-pub fn save_image(data_image: &DataImage, name: &str, index: u64) {
+/**
+ * Format of the colored image
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OutputFormat {
+    Png,
+    // quality 1 to 100
+    Jpg(u8),
+    // lossless
+    WebP,
+}
+
+/**
+ * Where and how are result images saved
+ * template placeholders
+ * - {name}       fractal name in snake case
+ * - {index}      frame number, {index:5} zero padded to 5 digits
+ * - {timestamp}  local time of saving, as 20250131_235959
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct OutputFiles {
+    // created if missing
+    pub dir: &'static str,
+    pub template: &'static str,
+    pub format: OutputFormat,
+}
+
+pub const DEFAULT_OUTPUT_FILES: OutputFiles = OutputFiles {
+    dir: ".",
+    template: "{name}_{index}",
+    format: OutputFormat::Jpg(75),
+};
+
+impl OutputFiles {
+    /**
+     * Path of result file, suffix distinguishes more outputs of the same frame
+     */
+    pub fn path(&self, name: &str, index: u64, suffix: &str, extension: &str) -> Result<String, String> {
+        fs::create_dir_all(self.dir).map_err(|e| format!("{}: {}", self.dir, e))?;
+        let file_name = format!("{}{}.{}", fill_template(self.template, name, index)?, suffix, extension);
+        Ok(Path::new(self.dir).join(file_name).to_string_lossy().into_owned())
+    }
+}
+
+fn fill_template(template: &str, name: &str, index: u64) -> Result<String, String> {
+    let mut result = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        result.push_str(&rest[..start]);
+        let end = rest[start..]
+            .find('}')
+            .ok_or(format!("unclosed placeholder in '{}'", template))?;
+        let placeholder = &rest[start + 1..start + end];
+        match placeholder.split_once(':') {
+            None if placeholder == "name" => result.push_str(&to_snake(name)),
+            None if placeholder == "index" => result.push_str(&index.to_string()),
+            None if placeholder == "timestamp" => {
+                result.push_str(&chrono::Local::now().format("%Y%m%d_%H%M%S").to_string())
+            }
+            Some(("index", padding)) => {
+                let width: usize = padding
+                    .parse()
+                    .map_err(|_| format!("invalid padding '{}'", padding))?;
+                result.push_str(&format!("{:0width$}", index, width = width));
+            }
+            _ => return Err(format!("unknown placeholder '{{{}}}'", placeholder)),
+        }
+        rest = &rest[start + end + 1..];
+    }
+    result.push_str(rest);
+    Ok(result)
+}
+
+/**
+ * Raw pixel values, unmapped by palette, for coloring outside of the application
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DensityOutput {
+    Nothing,
+    // grayscale, values above u16::MAX are clipped
+    Png16,
+    Tiff16,
+    // grayscale, values above u32::MAX are clipped
+    Tiff32,
+}
+
+/**
+ * Floating point output of density and of colored image, values are not clipped
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum HdrOutput {
+    Nothing,
+    OpenExr,
+    // Radiance .hdr
+    Radiance,
+}
+
+/**
+ * Which density value becomes 1.0 in HDR output
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Normalization {
+    // path element counts as they are
+    Raw,
+    Max,
+    // median of the non-zero values
+    Median,
+}
+
+/**
+ * HDR density exposure, normalized value multiplied by 2^stops
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Exposure {
+    pub normalization: Normalization,
+    pub stops: f64,
+}
+
+pub const NORMALIZED_MAX: Exposure = Exposure {
+    normalization: Normalization::Max,
+    stops: 0.0,
+};
+
+/**
+ * Colored pixels, metadata as PNG text chunks or as JSON sidecar file for the other formats
+ */
+pub fn save_image(data_image: &DataImage, files: &OutputFiles, metadata: &Metadata) -> Result<(), String> {
     println!("save_image()");
+    let name = &metadata.name;
+    let index = metadata.frame;
 
-    let width = data_image.width_xl;
-    let height = data_image.height_yl;
+    let extension = match files.format {
+        OutputFormat::Png => "png",
+        OutputFormat::Jpg(_) => "jpg",
+        OutputFormat::WebP => "webp",
+    };
+    let path = files.path(name, index, "", extension)?;
+    println!("{}", path);
 
-    if width <= 600 {
-        println!("save_image() {} {} skip", width, height);
-        return;
+    let img = rgb_image(data_image);
+    match files.format {
+        OutputFormat::Png => {
+            return write_png(&path, &img, metadata);
+        }
+        OutputFormat::Jpg(quality) => {
+            let file = File::create(&path).map_err(|e| format!("{}: {}", path, e))?;
+            img.write_with_encoder(JpegEncoder::new_with_quality(BufWriter::new(file), quality))
+        }
+        OutputFormat::WebP => img.save_with_format(&path, ImageFormat::WebP),
     }
+    .map_err(|e| format!("{}: {}", path, e))?;
 
-    let path = format!("{}_{}.jpg", to_snake(name), index);
+    let sidecar = files.path(name, index, "", "json")?;
+    fs::write(&sidecar, metadata.to_json()).map_err(|e| format!("{}: {}", sidecar, e))?;
+    println!("save_image() done");
+    Ok(())
+}
+
+/**
+ * Small PNG of colored pixels, at most max_width wide, aspect ratio is kept
+ */
+pub fn save_preview(
+    data_image: &DataImage,
+    files: &OutputFiles,
+    name: &str,
+    index: u64,
+    max_width: usize,
+) -> Result<(), String> {
+    let path = files.path(name, index, "_preview", "png")?;
     println!("{}", path);
 
+    let img = rgb_image(data_image);
+    let img = if img.width() > max_width as u32 {
+        let height = (img.height() as u64 * max_width as u64 / img.width() as u64).max(1) as u32;
+        image::imageops::resize(&img, max_width as u32, height, FilterType::Triangle)
+    } else {
+        img
+    };
+    img.save_with_format(&path, ImageFormat::Png)
+        .map_err(|e| format!("{}: {}", path, e))
+}
+
+fn rgb_image(data_image: &DataImage) -> RgbImage {
+    let width = data_image.width_xl;
+    let height = data_image.height_yl;
     let mut img: RgbImage = ImageBuffer::new(width as u32, height as u32);
 
     // [0, height)
@@ -25,9 +206,120 @@ pub fn save_image(data_image: &DataImage, name: &str, index: u64) {
             }
         }
     }
+    img
+}
 
-    img.save(path).unwrap();
-    println!("save_image() done");
+/**
+ * RGB PNG with every metadata entry as a tEXt chunk
+ */
+fn write_png(path: &str, img: &RgbImage, metadata: &Metadata) -> Result<(), String> {
+    let file = File::create(path).map_err(|e| format!("{}: {}", path, e))?;
+    let mut encoder = png::Encoder::new(BufWriter::new(file), img.width(), img.height());
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    for (key, value) in metadata.entries() {
+        encoder
+            .add_text_chunk(key.to_string(), value)
+            .map_err(|e| format!("{}: {}", path, e))?;
+    }
+    encoder
+        .write_header()
+        .and_then(|mut writer| writer.write_image_data(img.as_raw()))
+        .map_err(|e| format!("{}: {}", path, e))
+}
+
+/**
+ * Raw values of pixels, how many path elements hit each pixel, as grayscale image
+ */
+pub fn save_density(
+    data_image: &DataImage,
+    files: &OutputFiles,
+    name: &str,
+    index: u64,
+    output: DensityOutput,
+) -> Result<(), String> {
+    let width = data_image.width_xl;
+    let height = data_image.height_yl;
+    let extension = match output {
+        DensityOutput::Nothing => return Ok(()),
+        DensityOutput::Png16 => "png",
+        DensityOutput::Tiff16 | DensityOutput::Tiff32 => "tif",
+    };
+    let path = files.path(name, index, "_density", extension)?;
+    println!("{}", path);
+
+    // [0, height)
+    let values: Vec<u64> = (0..height)
+        .flat_map(|y| (0..width).map(move |x| (x, y)))
+        .map(|(x, y)| data_image.value_at(x, y))
+        .collect();
+
+    if output == DensityOutput::Tiff32 {
+        let data: Vec<u32> = values.iter().map(|v| (*v).min(u32::MAX as u64) as u32).collect();
+        let file = File::create(&path).map_err(|e| format!("{}: {}", path, e))?;
+        return TiffEncoder::new(BufWriter::new(file))
+            .and_then(|mut tiff| tiff.write_image::<colortype::Gray32>(width as u32, height as u32, &data))
+            .map_err(|e| format!("{}: {}", path, e));
+    }
+    let data: Vec<u16> = values.iter().map(|v| (*v).min(u16::MAX as u64) as u16).collect();
+    let img: ImageBuffer<Luma<u16>, Vec<u16>> = ImageBuffer::from_raw(width as u32, height as u32, data)
+        .ok_or(format!("{}: wrong image size", path))?;
+    img.save(&path).map_err(|e| format!("{}: {}", path, e))
+}
+
+/**
+ * Density as gray and colored image as linear RGB, both floating point
+ */
+pub fn save_hdr(
+    data_image: &DataImage,
+    files: &OutputFiles,
+    name: &str,
+    index: u64,
+    output: HdrOutput,
+    exposure: Exposure,
+) -> Result<(), String> {
+    let extension = match output {
+        HdrOutput::Nothing => return Ok(()),
+        HdrOutput::OpenExr => "exr",
+        HdrOutput::Radiance => "hdr",
+    };
+    let width = data_image.width_xl;
+    let height = data_image.height_yl;
+    let values: Vec<f64> = (0..height)
+        .flat_map(|y| (0..width).map(move |x| (x, y)))
+        .map(|(x, y)| data_image.value_at(x, y) as f64)
+        .collect();
+    let scale = exposure_scale(&values, exposure);
+
+    let path = files.path(name, index, "_density", extension)?;
+    println!("{}", path);
+    let density: Vec<f32> = values.iter().flat_map(|v| [(v * scale) as f32; 3]).collect();
+    let img = Rgb32FImage::from_raw(width as u32, height as u32, density).ok_or(format!("{}: wrong image size", path))?;
+    img.save(&path).map_err(|e| format!("{}: {}", path, e))?;
+
+    let path = files.path(name, index, "", extension)?;
+    println!("{}", path);
+    let mut img = Rgb32FImage::new(width as u32, height as u32);
+    for y in 0..height {
+        for x in 0..width {
+            if let Some(color) = data_image.color_at(x, y) {
+                img.put_pixel(x as u32, y as u32, Rgb(srgb_to_linear(color).map(|c| c as f32)));
+            }
+        }
+    }
+    img.save(&path).map_err(|e| format!("{}: {}", path, e))
+}
+
+fn exposure_scale(values: &[f64], exposure: Exposure) -> f64 {
+    let non_zero: Vec<f64> = values.iter().copied().filter(|v| *v > 0.0).collect();
+    let (max, median) = max_and_median(non_zero);
+    let reference = match exposure.normalization {
+        Normalization::Raw => 1.0,
+        Normalization::Max => max,
+        Normalization::Median => median,
+    };
+    let reference = if reference > 0.0 { reference } else { 1.0 };
+    2f64.powf(exposure.stops) / reference
 }
 
 fn to_snake(s: &str) -> String {
@@ -36,10 +328,18 @@ fn to_snake(s: &str) -> String {
 
 #[cfg(test)]
 mod tests {
-    use crate::files::{save_image, to_snake};
+    use crate::files::DensityOutput::{Png16, Tiff16, Tiff32};
+    use crate::files::HdrOutput::{OpenExr, Radiance};
+    use crate::files::Normalization::{Max, Median, Raw};
+    use crate::files::OutputFormat::{Jpg, Png, WebP};
+    use crate::files::{
+        exposure_scale, fill_template, save_density, save_hdr, save_image, save_preview,
+        to_snake, Exposure, OutputFiles, DEFAULT_OUTPUT_FILES, NORMALIZED_MAX,
+    };
     use crate::fractal::init_trivial_dynamic_config;
+    use crate::metadata::init_trivial;
     use crate::{area, data_image};
-    use image::DynamicImage;
+    use image::{DynamicImage, Rgb};
 
     #[test]
     fn test_save_image() {
@@ -47,7 +347,7 @@ mod tests {
         let c = init_trivial_dynamic_config(621);
         let a = area::init(&c);
 
-        save_image(&data_image::init(&c, &a), fractal_name, 0);
+        save_image(&data_image::init(&c, &a), &DEFAULT_OUTPUT_FILES, &init_trivial(fractal_name, 0)).unwrap();
 
         let file_name = "fractal_snake_0.jpg";
         assert!(std::fs::metadata(file_name).unwrap().is_file());
@@ -56,7 +356,170 @@ mod tests {
         assert_eq!(img.width(), 620);
         assert_eq!(img.height(), 620);
 
+        // metadata sidecar
+        let json = std::fs::read_to_string("fractal_snake_0.json").unwrap();
+        assert!(json.contains("\"name\": \"Fractal Snake\""));
+
         std::fs::remove_file(file_name).unwrap();
+        std::fs::remove_file("fractal_snake_0.json").unwrap();
+    }
+
+    #[test]
+    fn test_save_image_small() {
+        let c = init_trivial_dynamic_config(11);
+        let a = area::init(&c);
+
+        save_image(&data_image::init(&c, &a), &DEFAULT_OUTPUT_FILES, &init_trivial("Fractal Small", 0)).unwrap();
+
+        let file_name = "fractal_small_0.jpg";
+        assert_eq!(image::open(file_name).unwrap().width(), 10);
+        std::fs::remove_file(file_name).unwrap();
+        std::fs::remove_file("fractal_small_0.json").unwrap();
+    }
+
+    #[test]
+    fn test_save_preview() {
+        let c = init_trivial_dynamic_config(21);
+        let a = area::init(&c);
+        let di = data_image::init(&c, &a);
+
+        save_preview(&di, &DEFAULT_OUTPUT_FILES, "Fractal Thumb", 0, 5).unwrap();
+        let img = image::open("fractal_thumb_0_preview.png").unwrap();
+        assert_eq!((img.width(), img.height()), (5, 5));
+
+        // smaller images are not enlarged
+        save_preview(&di, &DEFAULT_OUTPUT_FILES, "Fractal Thumb", 1, 100).unwrap();
+        let img = image::open("fractal_thumb_1_preview.png").unwrap();
+        assert_eq!(img.width(), 20);
+
+        std::fs::remove_file("fractal_thumb_0_preview.png").unwrap();
+        std::fs::remove_file("fractal_thumb_1_preview.png").unwrap();
+    }
+
+    #[test]
+    fn test_save_image_formats() {
+        let c = init_trivial_dynamic_config(621);
+        let a = area::init(&c);
+        let di = data_image::init(&c, &a);
+
+        for (format, file_name) in [
+            (Png, "test_output_formats/fractal_formats_007.png"),
+            (Jpg(95), "test_output_formats/fractal_formats_007.jpg"),
+            (WebP, "test_output_formats/fractal_formats_007.webp"),
+        ] {
+            let files = OutputFiles { dir: "test_output_formats", template: "fractal_{name}_{index:3}", format };
+            save_image(&di, &files, &init_trivial("Formats", 7)).unwrap();
+            assert_eq!(image::open(file_name).unwrap().width(), 620);
+        }
+
+        // PNG carries metadata inside, the others in sidecar
+        assert!(std::fs::metadata("test_output_formats/fractal_formats_007.json").is_ok());
+        std::fs::remove_dir_all("test_output_formats").unwrap();
+    }
+
+    #[test]
+    fn test_save_image_png_metadata() {
+        let c = init_trivial_dynamic_config(5);
+        let a = area::init(&c);
+        let files = OutputFiles { dir: "test_output_metadata", template: "{name}", format: Png };
+        let mut metadata = init_trivial("Fractal Text", 0);
+        metadata.center_re = -0.75;
+
+        save_image(&data_image::init(&c, &a), &files, &metadata).unwrap();
+
+        let file = std::fs::File::open("test_output_metadata/fractal_text.png").unwrap();
+        let reader = png::Decoder::new(std::io::BufReader::new(file)).read_info().unwrap();
+        let text = &reader.info().uncompressed_latin1_text;
+        assert_eq!(text.len(), metadata.entries().len());
+        assert!(text.iter().any(|t| t.keyword == "center_re" && t.text == "-0.75"));
+        assert!(std::fs::metadata("test_output_metadata/fractal_text.json").is_err());
+        std::fs::remove_dir_all("test_output_metadata").unwrap();
+    }
+
+    #[test]
+    fn test_output_files_path() {
+        let files = OutputFiles { dir: "test_output_path/frames", template: "{name}-{index:4}", format: Png };
+
+        let path = files.path("Fat Star", 12, "_density", "tif").unwrap();
+
+        assert_eq!(path, "test_output_path/frames/fat_star-0012_density.tif");
+        assert!(std::fs::metadata("test_output_path/frames").unwrap().is_dir());
+        std::fs::remove_dir_all("test_output_path").unwrap();
+    }
+
+    #[test]
+    fn test_fill_template() {
+        assert_eq!(fill_template("{name}_{index}", "Nebula Top", 3).unwrap(), "nebula_top_3");
+        assert_eq!(fill_template("frame{index:5}", "Nebula", 42).unwrap(), "frame00042");
+        assert_eq!(fill_template("{timestamp}", "Nebula", 0).unwrap().len(), 15);
+        assert!(fill_template("{size}", "Nebula", 0).is_err());
+        assert!(fill_template("{index:x}", "Nebula", 0).is_err());
+        assert!(fill_template("{name", "Nebula", 0).is_err());
+    }
+
+    #[test]
+    fn test_save_density() {
+        let c = init_trivial_dynamic_config(5);
+        let a = area::init(&c);
+        let di = data_image::init(&c, &a);
+        di.set(3, 1, 70_000);
+
+        save_density(&di, &DEFAULT_OUTPUT_FILES, "Fractal Raw", 0, Png16).unwrap();
+        let img = image::open("fractal_raw_0_density.png").unwrap().into_luma16();
+        assert_eq!(img.get_pixel(3, 1).0, [u16::MAX]);
+        assert_eq!(img.get_pixel(0, 0).0, [0]);
+        std::fs::remove_file("fractal_raw_0_density.png").unwrap();
+
+        save_density(&di, &DEFAULT_OUTPUT_FILES, "Fractal Raw", 1, Tiff16).unwrap();
+        let img = image::open("fractal_raw_1_density.tif").unwrap().into_luma16();
+        assert_eq!(img.get_pixel(3, 1).0, [u16::MAX]);
+        std::fs::remove_file("fractal_raw_1_density.tif").unwrap();
+
+        save_density(&di, &DEFAULT_OUTPUT_FILES, "Fractal Raw", 2, Tiff32).unwrap();
+        let file = std::fs::File::open("fractal_raw_2_density.tif").unwrap();
+        let mut decoder = tiff::decoder::Decoder::new(std::io::BufReader::new(file)).unwrap();
+        assert_eq!(decoder.dimensions().unwrap(), (4, 4));
+        match decoder.read_image().unwrap() {
+            tiff::decoder::DecodingResult::U32(data) => assert_eq!(data[3 + 4], 70_000),
+            _ => panic!("expected 32-bit values"),
+        }
+        std::fs::remove_file("fractal_raw_2_density.tif").unwrap();
+    }
+
+    #[test]
+    fn test_save_hdr() {
+        let c = init_trivial_dynamic_config(5);
+        let a = area::init(&c);
+        let di = data_image::init(&c, &a);
+        di.set(3, 1, 1_000_000);
+        di.set(0, 2, 250_000);
+        di.color(1, 2, Rgb([255, 0, 0]));
+
+        save_hdr(&di, &DEFAULT_OUTPUT_FILES, "Fractal Hdr", 0, OpenExr, NORMALIZED_MAX).unwrap();
+        let img = image::open("fractal_hdr_0_density.exr").unwrap().into_rgb32f();
+        assert_eq!(img.get_pixel(3, 1).0, [1.0, 1.0, 1.0]);
+        assert_eq!(img.get_pixel(0, 2).0, [0.25, 0.25, 0.25]);
+        let img = image::open("fractal_hdr_0.exr").unwrap().into_rgb32f();
+        assert_eq!(img.get_pixel(1, 2).0, [1.0, 0.0, 0.0]);
+        std::fs::remove_file("fractal_hdr_0_density.exr").unwrap();
+        std::fs::remove_file("fractal_hdr_0.exr").unwrap();
+
+        let raw = Exposure { normalization: Raw, stops: 0.0 };
+        save_hdr(&di, &DEFAULT_OUTPUT_FILES, "Fractal Hdr", 1, Radiance, raw).unwrap();
+        let img = image::open("fractal_hdr_1_density.hdr").unwrap().into_rgb32f();
+        assert!((img.get_pixel(3, 1)[0] - 1_000_000.0).abs() < 10_000.0);
+        std::fs::remove_file("fractal_hdr_1_density.hdr").unwrap();
+        std::fs::remove_file("fractal_hdr_1.hdr").unwrap();
+    }
+
+    #[test]
+    fn test_exposure_scale() {
+        let values = [0.0, 0.0, 2.0, 4.0, 8.0];
+
+        assert_eq!(exposure_scale(&values, NORMALIZED_MAX), 0.125);
+        assert_eq!(exposure_scale(&values, Exposure { normalization: Median, stops: 1.0 }), 0.5);
+        assert_eq!(exposure_scale(&values, Exposure { normalization: Raw, stops: -1.0 }), 0.5);
+        assert_eq!(exposure_scale(&[0.0], Exposure { normalization: Max, stops: 0.0 }), 1.0);
     }
 
     #[test]
@@ -0,0 +1,777 @@
+use crate::fractal::FractalMath;
+use crate::mathematician::Sequence;
+use crate::mem::Mem;
+use std::fs;
+use std::str::FromStr;
+
+// z, c, n and the formula's own variables
+const MAX_VARIABLES: usize = 16;
+const Z: usize = 0;
+const C: usize = 1;
+const N: usize = 2;
+
+/**
+ * Fractal defined by formula text instead of Rust code
+ *
+ * z = z^2 + c; if prime(n) { z = 0.01 / z }
+ *
+ * - z is the calculation result, c is the origin, n is the iteration number
+ * - n is increased before each evaluation, same as Mem::euler()
+ * - operators + - * / ^, constant i, numbers like 0.5 or 2i
+ * - functions conj, abs, re, im, arg, sqrt, exp, log, sin, cos
+ * - conditions compare real parts: < > <= >= == !=, combine with && || !
+ * - sequence predicates prime, fibonacci, perfect, square, triangular, lucas, lazy, happy
+ * - # comments till the end of line
+ * - degree of the map isn't known, smooth coloring uses degree 2
+ */
+pub struct Formula {
+    statements: Vec<Stmt>,
+    sequences: Vec<Sequence>,
+    // the formula text, for image metadata
+    source: String,
+}
+
+impl Formula {
+    pub fn from_file(path: &str) -> Result<Self, String> {
+        let content = fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+        content.parse()
+    }
+
+    /**
+     * Initialize mathematician sequences used by the formula
+     */
+    pub fn init_sequences(&self, max: u64) {
+        for s in &self.sequences {
+            s.init(max);
+        }
+    }
+}
+
+impl FromStr for Formula {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let tokens = tokenize(s)?;
+        let mut parser = Parser {
+            tokens,
+            position: 0,
+            variables: vec!["z".to_string(), "c".to_string(), "n".to_string()],
+            sequences: Vec::new(),
+        };
+        let mut statements = Vec::new();
+        while !parser.is_end() {
+            statements.push(parser.statement()?);
+        }
+        if statements.is_empty() {
+            return Err("empty formula".to_string());
+        }
+        Ok(Formula {
+            statements,
+            sequences: parser.sequences,
+            source: s.to_string(),
+        })
+    }
+}
+
+impl FractalMath<Mem> for Formula {
+    fn math(&self, m: &mut Mem, origin_re: f64, origin_im: f64) {
+        m.it += 1;
+        let mut vars = [Value::ZERO; MAX_VARIABLES];
+        vars[Z] = Value::new(m.re, m.im);
+        vars[C] = Value::new(origin_re, origin_im);
+        vars[N] = Value::new(m.it as f64, 0.0);
+
+        execute(&self.statements, &mut vars);
+
+        m.re = vars[Z].re;
+        m.im = vars[Z].im;
+    }
+
+    fn definition(&self) -> Option<String> {
+        Some(self.source.clone())
+    }
+}
+
+/* ----------------
+ * Complex numbers
+ * ------------- */
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Value {
+    re: f64,
+    im: f64,
+}
+
+impl Value {
+    const ZERO: Value = Value { re: 0.0, im: 0.0 };
+    const ONE: Value = Value { re: 1.0, im: 0.0 };
+
+    fn new(re: f64, im: f64) -> Value {
+        Value { re, im }
+    }
+
+    fn add(self, o: Value) -> Value {
+        Value::new(self.re + o.re, self.im + o.im)
+    }
+
+    fn sub(self, o: Value) -> Value {
+        Value::new(self.re - o.re, self.im - o.im)
+    }
+
+    fn mul(self, o: Value) -> Value {
+        Value::new(
+            self.re * o.re - self.im * o.im,
+            self.re * o.im + self.im * o.re,
+        )
+    }
+
+    fn div(self, o: Value) -> Value {
+        let q = o.quad();
+        Value::new(
+            (self.re * o.re + self.im * o.im) / q,
+            (self.im * o.re - self.re * o.im) / q,
+        )
+    }
+
+    fn quad(self) -> f64 {
+        self.re * self.re + self.im * self.im
+    }
+
+    fn abs(self) -> f64 {
+        self.quad().sqrt()
+    }
+
+    fn arg(self) -> f64 {
+        self.im.atan2(self.re)
+    }
+
+    fn exp(self) -> Value {
+        let e = self.re.exp();
+        Value::new(e * self.im.cos(), e * self.im.sin())
+    }
+
+    fn log(self) -> Value {
+        Value::new(self.abs().ln(), self.arg())
+    }
+
+    fn pow(self, e: Value) -> Value {
+        if e.im == 0.0 && e.re.fract() == 0.0 && e.re.abs() <= 64.0 {
+            // exact integer power by repeated squaring
+            let mut n = e.re.abs() as u32;
+            let mut base = self;
+            let mut r = Value::ONE;
+            while n > 0 {
+                if n & 1 == 1 {
+                    r = r.mul(base);
+                }
+                base = base.mul(base);
+                n >>= 1;
+            }
+            if e.re < 0.0 { Value::ONE.div(r) } else { r }
+        } else if self == Value::ZERO {
+            Value::ZERO
+        } else {
+            e.mul(self.log()).exp()
+        }
+    }
+}
+
+/* ---------------
+ * Syntax tree
+ * ------------ */
+
+enum Stmt {
+    Assign(usize, Expr),
+    If(Cond, Vec<Stmt>, Vec<Stmt>),
+}
+
+enum Expr {
+    Number(Value),
+    Variable(usize),
+    Neg(Box<Expr>),
+    Binary(Op, Box<Expr>, Box<Expr>),
+    Function(Function, Box<Expr>),
+}
+
+#[derive(Clone, Copy)]
+enum Op {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Pow,
+}
+
+#[derive(Clone, Copy)]
+enum Function {
+    Conj,
+    Abs,
+    Re,
+    Im,
+    Arg,
+    Sqrt,
+    Exp,
+    Log,
+    Sin,
+    Cos,
+}
+
+enum Cond {
+    Compare(Cmp, Expr, Expr),
+    Is(Sequence, Expr),
+    Not(Box<Cond>),
+    And(Box<Cond>, Box<Cond>),
+    Or(Box<Cond>, Box<Cond>),
+}
+
+#[derive(Clone, Copy)]
+enum Cmp {
+    Lt,
+    Gt,
+    Le,
+    Ge,
+    Eq,
+    Ne,
+}
+
+fn function_by_name(name: &str) -> Option<Function> {
+    match name {
+        "conj" => Some(Function::Conj),
+        "abs" => Some(Function::Abs),
+        "re" => Some(Function::Re),
+        "im" => Some(Function::Im),
+        "arg" => Some(Function::Arg),
+        "sqrt" => Some(Function::Sqrt),
+        "exp" => Some(Function::Exp),
+        "log" => Some(Function::Log),
+        "sin" => Some(Function::Sin),
+        "cos" => Some(Function::Cos),
+        _ => None,
+    }
+}
+
+fn sequence_by_name(name: &str) -> Option<Sequence> {
+    match name.strip_prefix("is_").unwrap_or(name) {
+        "prime" => Some(Sequence::Prime),
+        "fibonacci" => Some(Sequence::Fibonacci),
+        "perfect" => Some(Sequence::Perfect),
+        "square" => Some(Sequence::Square),
+        "triangular" => Some(Sequence::Triangular),
+        "lucas" => Some(Sequence::Lucas),
+        "lazy" => Some(Sequence::Lazy),
+        "happy" => Some(Sequence::Happy),
+        _ => None,
+    }
+}
+
+/* -------------
+ * Interpreter
+ * ---------- */
+
+fn execute(statements: &[Stmt], vars: &mut [Value; MAX_VARIABLES]) {
+    for s in statements {
+        match s {
+            Stmt::Assign(slot, e) => vars[*slot] = evaluate(e, vars),
+            Stmt::If(cond, then, otherwise) => {
+                if test(cond, vars) {
+                    execute(then, vars);
+                } else {
+                    execute(otherwise, vars);
+                }
+            }
+        }
+    }
+}
+
+fn evaluate(e: &Expr, vars: &[Value; MAX_VARIABLES]) -> Value {
+    match e {
+        Expr::Number(v) => *v,
+        Expr::Variable(slot) => vars[*slot],
+        Expr::Neg(a) => Value::ZERO.sub(evaluate(a, vars)),
+        Expr::Binary(op, a, b) => {
+            let a = evaluate(a, vars);
+            let b = evaluate(b, vars);
+            match op {
+                Op::Add => a.add(b),
+                Op::Sub => a.sub(b),
+                Op::Mul => a.mul(b),
+                Op::Div => a.div(b),
+                Op::Pow => a.pow(b),
+            }
+        }
+        Expr::Function(f, a) => {
+            let a = evaluate(a, vars);
+            match f {
+                Function::Conj => Value::new(a.re, -a.im),
+                Function::Abs => Value::new(a.abs(), 0.0),
+                Function::Re => Value::new(a.re, 0.0),
+                Function::Im => Value::new(a.im, 0.0),
+                Function::Arg => Value::new(a.arg(), 0.0),
+                Function::Sqrt => a.pow(Value::new(0.5, 0.0)),
+                Function::Exp => a.exp(),
+                Function::Log => a.log(),
+                Function::Sin => Value::new(a.re.sin() * a.im.cosh(), a.re.cos() * a.im.sinh()),
+                Function::Cos => Value::new(a.re.cos() * a.im.cosh(), -a.re.sin() * a.im.sinh()),
+            }
+        }
+    }
+}
+
+fn test(cond: &Cond, vars: &[Value; MAX_VARIABLES]) -> bool {
+    match cond {
+        Cond::Compare(cmp, a, b) => {
+            let a = evaluate(a, vars).re;
+            let b = evaluate(b, vars).re;
+            match cmp {
+                Cmp::Lt => a < b,
+                Cmp::Gt => a > b,
+                Cmp::Le => a <= b,
+                Cmp::Ge => a >= b,
+                Cmp::Eq => a == b,
+                Cmp::Ne => a != b,
+            }
+        }
+        Cond::Is(sequence, a) => {
+            let v = evaluate(a, vars).re.round();
+            if !(v >= 0.0 && v < u64::MAX as f64) {
+                return false;
+            }
+            sequence.contains(v as u64)
+        }
+        Cond::Not(a) => !test(a, vars),
+        Cond::And(a, b) => test(a, vars) && test(b, vars),
+        Cond::Or(a, b) => test(a, vars) || test(b, vars),
+    }
+}
+
+/* -------------
+ * Tokenizer
+ * ---------- */
+
+#[derive(Clone, PartialEq, Debug)]
+enum Token {
+    Number(Value),
+    Ident(String),
+    Symbol(&'static str),
+}
+
+const SYMBOLS: [&str; 20] = [
+    "<=", ">=", "==", "!=", "&&", "||", "+", "-", "*", "/", "^", "(", ")", "{", "}", ";", "=", "<",
+    ">", "!",
+];
+
+fn tokenize(s: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let chars: Vec<char> = s.chars().collect();
+    let mut i = 0;
+    while i < chars.len() {
+        let ch = chars[i];
+        if ch.is_whitespace() {
+            i += 1;
+        } else if ch == '#' {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+        } else if ch.is_ascii_digit() || ch == '.' {
+            let start = i;
+            while i < chars.len()
+                && (chars[i].is_ascii_digit()
+                    || chars[i] == '.'
+                    || chars[i] == 'e'
+                    || ((chars[i] == '-' || chars[i] == '+') && chars[i - 1] == 'e'))
+            {
+                i += 1;
+            }
+            let text: String = chars[start..i].iter().collect();
+            let v = text
+                .parse::<f64>()
+                .map_err(|_| format!("invalid number '{}'", text))?;
+            // imaginary literal like 2i
+            let imaginary = i < chars.len()
+                && chars[i] == 'i'
+                && !chars.get(i + 1).is_some_and(|c| c.is_alphanumeric() || *c == '_');
+            if imaginary {
+                i += 1;
+                tokens.push(Token::Number(Value::new(0.0, v)));
+            } else {
+                tokens.push(Token::Number(Value::new(v, 0.0)));
+            }
+        } else if ch.is_alphabetic() || ch == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            tokens.push(Token::Ident(chars[start..i].iter().collect()));
+        } else {
+            let rest: String = chars[i..chars.len().min(i + 2)].iter().collect();
+            match SYMBOLS.iter().find(|s| rest.starts_with(**s)) {
+                Some(symbol) => {
+                    i += symbol.len();
+                    tokens.push(Token::Symbol(symbol));
+                }
+                None => return Err(format!("unexpected character '{}'", ch)),
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+/* -------------
+ * Parser
+ * ---------- */
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+    variables: Vec<String>,
+    sequences: Vec<Sequence>,
+}
+
+impl Parser {
+    fn is_end(&self) -> bool {
+        self.position >= self.tokens.len()
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Result<Token, String> {
+        let t = self
+            .tokens
+            .get(self.position)
+            .cloned()
+            .ok_or("unexpected end of formula")?;
+        self.position += 1;
+        Ok(t)
+    }
+
+    fn is_symbol(&self, symbol: &str) -> bool {
+        matches!(self.peek(), Some(Token::Symbol(s)) if *s == symbol)
+    }
+
+    fn accept(&mut self, symbol: &str) -> bool {
+        if self.is_symbol(symbol) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, symbol: &str) -> Result<(), String> {
+        match self.next()? {
+            Token::Symbol(s) if s == symbol => Ok(()),
+            t => Err(format!("expected '{}', found {:?}", symbol, t)),
+        }
+    }
+
+    fn statement(&mut self) -> Result<Stmt, String> {
+        let name = match self.next()? {
+            Token::Ident(name) => name,
+            t => return Err(format!("expected statement, found {:?}", t)),
+        };
+        if name == "if" {
+            return self.if_statement();
+        }
+        self.expect("=")?;
+        let e = self.expression()?;
+        self.accept(";");
+        Ok(Stmt::Assign(self.assignable(&name)?, e))
+    }
+
+    fn if_statement(&mut self) -> Result<Stmt, String> {
+        let cond = self.condition()?;
+        let then = self.block()?;
+        let mut otherwise = Vec::new();
+        if matches!(self.peek(), Some(Token::Ident(name)) if name == "else") {
+            self.position += 1;
+            if matches!(self.peek(), Some(Token::Ident(name)) if name == "if") {
+                self.position += 1;
+                otherwise.push(self.if_statement()?);
+            } else {
+                otherwise = self.block()?;
+            }
+        }
+        Ok(Stmt::If(cond, then, otherwise))
+    }
+
+    fn block(&mut self) -> Result<Vec<Stmt>, String> {
+        self.expect("{")?;
+        let mut statements = Vec::new();
+        while !self.accept("}") {
+            if self.is_end() {
+                return Err("missing '}'".to_string());
+            }
+            statements.push(self.statement()?);
+        }
+        Ok(statements)
+    }
+
+    fn assignable(&mut self, name: &str) -> Result<usize, String> {
+        if name == "c" || name == "n" || name == "i" || is_reserved(name) {
+            return Err(format!("can't assign to '{}'", name));
+        }
+        match self.variables.iter().position(|v| v == name) {
+            Some(slot) => Ok(slot),
+            None if self.variables.len() < MAX_VARIABLES => {
+                self.variables.push(name.to_string());
+                Ok(self.variables.len() - 1)
+            }
+            None => Err(format!("too many variables, max {}", MAX_VARIABLES)),
+        }
+    }
+
+    fn condition(&mut self) -> Result<Cond, String> {
+        let mut a = self.condition_and()?;
+        while self.accept("||") {
+            let b = self.condition_and()?;
+            a = Cond::Or(Box::new(a), Box::new(b));
+        }
+        Ok(a)
+    }
+
+    fn condition_and(&mut self) -> Result<Cond, String> {
+        let mut a = self.condition_atom()?;
+        while self.accept("&&") {
+            let b = self.condition_atom()?;
+            a = Cond::And(Box::new(a), Box::new(b));
+        }
+        Ok(a)
+    }
+
+    fn condition_atom(&mut self) -> Result<Cond, String> {
+        if self.accept("!") {
+            return Ok(Cond::Not(Box::new(self.condition_atom()?)));
+        }
+        if let Some(Token::Ident(name)) = self.peek()
+            && let Some(sequence) = sequence_by_name(name)
+        {
+            self.position += 1;
+            self.expect("(")?;
+            let e = self.expression()?;
+            self.expect(")")?;
+            if !self.sequences.contains(&sequence) {
+                self.sequences.push(sequence);
+            }
+            return Ok(Cond::Is(sequence, e));
+        }
+        if self.is_symbol("(") {
+            // parenthesized condition, or an expression in parentheses
+            let start = self.position;
+            self.position += 1;
+            if let Ok(cond) = self.condition()
+                && self.accept(")")
+            {
+                return Ok(cond);
+            }
+            self.position = start;
+        }
+        let a = self.expression()?;
+        let cmp = match self.next()? {
+            Token::Symbol("<") => Cmp::Lt,
+            Token::Symbol(">") => Cmp::Gt,
+            Token::Symbol("<=") => Cmp::Le,
+            Token::Symbol(">=") => Cmp::Ge,
+            Token::Symbol("==") => Cmp::Eq,
+            Token::Symbol("!=") => Cmp::Ne,
+            t => return Err(format!("expected comparison, found {:?}", t)),
+        };
+        let b = self.expression()?;
+        Ok(Cond::Compare(cmp, a, b))
+    }
+
+    fn expression(&mut self) -> Result<Expr, String> {
+        let mut a = self.term()?;
+        loop {
+            let op = if self.accept("+") {
+                Op::Add
+            } else if self.accept("-") {
+                Op::Sub
+            } else {
+                return Ok(a);
+            };
+            let b = self.term()?;
+            a = Expr::Binary(op, Box::new(a), Box::new(b));
+        }
+    }
+
+    fn term(&mut self) -> Result<Expr, String> {
+        let mut a = self.unary()?;
+        loop {
+            let op = if self.accept("*") {
+                Op::Mul
+            } else if self.accept("/") {
+                Op::Div
+            } else {
+                return Ok(a);
+            };
+            let b = self.unary()?;
+            a = Expr::Binary(op, Box::new(a), Box::new(b));
+        }
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        if self.accept("-") {
+            return Ok(Expr::Neg(Box::new(self.unary()?)));
+        }
+        let a = self.atom()?;
+        if self.accept("^") {
+            // right associative, z^2^3 = z^(2^3)
+            let b = self.unary()?;
+            return Ok(Expr::Binary(Op::Pow, Box::new(a), Box::new(b)));
+        }
+        Ok(a)
+    }
+
+    fn atom(&mut self) -> Result<Expr, String> {
+        match self.next()? {
+            Token::Number(v) => Ok(Expr::Number(v)),
+            Token::Symbol("(") => {
+                let e = self.expression()?;
+                self.expect(")")?;
+                Ok(e)
+            }
+            Token::Ident(name) => {
+                if let Some(f) = function_by_name(&name) {
+                    self.expect("(")?;
+                    let e = self.expression()?;
+                    self.expect(")")?;
+                    return Ok(Expr::Function(f, Box::new(e)));
+                }
+                if let Some(slot) = self.variables.iter().position(|v| *v == name) {
+                    return Ok(Expr::Variable(slot));
+                }
+                if name == "i" {
+                    return Ok(Expr::Number(Value::new(0.0, 1.0)));
+                }
+                Err(format!("unknown variable '{}'", name))
+            }
+            t => Err(format!("unexpected {:?}", t)),
+        }
+    }
+}
+
+fn is_reserved(name: &str) -> bool {
+    name == "if"
+        || name == "else"
+        || function_by_name(name).is_some()
+        || sequence_by_name(name).is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::formula::Formula;
+    use crate::fractal::{FractalMath, MemType};
+    use crate::mathematician;
+    use crate::mem::Mem;
+
+    fn run(formula: &str, re: f64, im: f64, origin_re: f64, origin_im: f64) -> Mem {
+        let f: Formula = formula.parse().unwrap();
+        let mut m = Mem::new(re, im);
+        f.math(&mut m, origin_re, origin_im);
+        m
+    }
+
+    #[test]
+    fn test_mandelbrot() {
+        let m = run("z = z^2 + c", 3.0, 2.0, 0.5, 0.4);
+        assert_eq!(m.re, 5.5);
+        assert_eq!(m.im, 12.4);
+        assert_eq!(m.it, 1);
+    }
+
+    #[test]
+    fn test_definition() {
+        let text = "# burning ship\nz = (abs(re(z)) + abs(im(z)) * i)^2 + c\n";
+        let f: Formula = text.parse().unwrap();
+        assert_eq!(f.definition().unwrap(), text);
+    }
+
+    #[test]
+    fn test_precedence() {
+        let m = run("z = 1 + 2 * 3 ^ 2 - -1", 0.0, 0.0, 0.0, 0.0);
+        assert_eq!(m.re, 20.0);
+
+        let m = run("z = (1 + 2) * 3", 0.0, 0.0, 0.0, 0.0);
+        assert_eq!(m.re, 9.0);
+    }
+
+    #[test]
+    fn test_complex() {
+        let m = run("z = 1 / z", 0.5, 0.5, 0.0, 0.0);
+        assert_eq!(m.re, 1.0);
+        assert_eq!(m.im, -1.0);
+
+        let m = run("z = conj(z) + 2i", 3.0, 2.0, 0.0, 0.0);
+        assert_eq!(m.re, 3.0);
+        assert_eq!(m.im, 0.0);
+
+        let m = run("z = abs(z) + i * im(z)", 3.0, 4.0, 0.0, 0.0);
+        assert_eq!(m.re, 5.0);
+        assert_eq!(m.im, 4.0);
+    }
+
+    #[test]
+    fn test_power() {
+        let m = run("z = z^3", 0.5, 0.5, 0.0, 0.0);
+        assert_eq!(m.re, -0.25);
+        assert_eq!(m.im, 0.25);
+
+        let m = run("z = z^-1", 2.0, 0.0, 0.0, 0.0);
+        assert_eq!(m.re, 0.5);
+
+        let m = run("z = z^0.5", 4.0, 0.0, 0.0, 0.0);
+        assert!((m.re - 2.0).abs() < 1e-12);
+        assert!(m.im.abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_variables() {
+        let m = run("w = z * 2; z = w + w", 1.0, 1.0, 0.0, 0.0);
+        assert_eq!(m.re, 4.0);
+        assert_eq!(m.im, 4.0);
+    }
+
+    #[test]
+    fn test_if_else() {
+        let f = "if abs(z) > 1 && !(re(z) < 0) { z = 0 } else { z = 1 }";
+        assert_eq!(run(f, 2.0, 0.0, 0.0, 0.0).re, 0.0);
+        assert_eq!(run(f, -2.0, 0.0, 0.0, 0.0).re, 1.0);
+        assert_eq!(run(f, 0.5, 0.0, 0.0, 0.0).re, 1.0);
+    }
+
+    #[test]
+    fn test_prime() {
+        mathematician::init_primes(3);
+        let f: Formula = "z = z^2 + c; if prime(n) { z = 0.01 / z }".parse().unwrap();
+        let mut m = Mem::new(0.0, 0.0);
+
+        // n = 1
+        f.math(&mut m, 0.1, 0.0);
+        assert!((m.re - 0.1).abs() < 1e-12);
+
+        // n = 2
+        f.math(&mut m, 0.1, 0.0);
+        assert!((m.re - 0.01 / 0.11).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_init_sequences() {
+        let f: Formula = "if is_happy(n) || lucas(n) { z = z }".parse().unwrap();
+        f.init_sequences(7);
+        assert!(mathematician::is_happy(7));
+        assert!(mathematician::is_lucas(7));
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!("".parse::<Formula>().is_err());
+        assert!("z = ".parse::<Formula>().is_err());
+        assert!("z = w".parse::<Formula>().is_err());
+        assert!("c = z".parse::<Formula>().is_err());
+        assert!("z = z $ 2".parse::<Formula>().is_err());
+        assert!("if z { z = 1 }".parse::<Formula>().is_err());
+        assert!("if z < 1 { z = 1".parse::<Formula>().is_err());
+    }
+}
@@ -1,16 +1,30 @@
 use crate::fractal::FractalCalculationType::{DynamicSequenceNebula, StaticSequenceMandelbrot};
-use crate::fractal::OrbitType::Finite;
+use crate::color_space::ColorSpace;
+use crate::color_space::ColorSpace::Srgb;
+use crate::coloring_mode::ColoringMode;
+use crate::coloring_mode::ColoringMode::Perfect;
+use crate::euler::{Channel, EULER_CHANNELS};
+use crate::files::{DensityOutput, Exposure, HdrOutput, OutputFiles, DEFAULT_OUTPUT_FILES, NORMALIZED_MAX};
+use crate::frame_sink::FrameSink;
+use crate::supersampling::Supersampling;
+use crate::tiles::Tiling;
+use crate::fractal::OrbitType::{Finite, Ignore};
 use crate::mathematician;
 use crate::mem::Mem;
+use crate::orbit_trap::OrbitTrap::Nothing as NoTrap;
+use crate::orbit_trap::OrbitTrapColoring::Instead;
+use crate::orbit_trap::{OrbitTrap, OrbitTrapColoring};
 use crate::palettes::PaletteName;
 use crate::palettes::PaletteName::Nothing;
 use crate::pixel_states::DomainElementState;
 use crate::pixel_states::DomainElementState::{ActiveNew, HibernatedDeepBlack};
 use crate::resolution_multiplier::ResolutionMultiplier;
 use crate::resolution_multiplier::ResolutionMultiplier::Single;
+use std::borrow::Cow;
 use std::cmp::PartialEq;
 use FractalCalculationType::StaticImageMandelbrot;
 
+#[derive(Clone)]
 pub struct FractalConfig {
     // fractal config
     pub name: &'static str,
@@ -22,6 +36,40 @@ pub struct FractalConfig {
     pub resolution_multiplier: ResolutionMultiplier,
     pub palette: PaletteName,
     pub palette_zero: PaletteName,
+    // color space in which palette colors are interpolated
+    pub palette_space: ColorSpace,
+    // palettes rotated by fraction of their length, as a frame of color cycling
+    pub palette_shift: f64,
+    pub coloring_mode: ColoringMode,
+    // static images, recolor the result with rotating palette, 0 for no animation
+    pub color_cycle_frames: u64,
+    // zoom videos, number of frames to follow the change of value distribution, 0 for no smoothing
+    pub coloring_time_constant: f64,
+    // euler specific, classifiers of path elements to spectral channels
+    pub spectral_channels: Cow<'static, [Channel]>,
+    // mandelbrot specific, closest distance of calculation path to the trap
+    pub orbit_trap: OrbitTrap,
+    pub orbit_trap_coloring: OrbitTrapColoring,
+    // mandelbrot specific, anti-aliasing by averaged colors of sub-samples of each pixel
+    pub supersampling: Supersampling,
+    // output, directory, file name template and format of colored image
+    pub output_files: OutputFiles,
+    // output, full size results are not saved if off or if narrower than min_save_width
+    pub save_images: bool,
+    pub min_save_width: usize,
+    // output, width of preview image saved always, 0 for no preview
+    pub preview_width: usize,
+    // output, raw pixel values for external coloring
+    pub density_output: DensityOutput,
+    // output, floating point density and colors, with density exposure
+    pub hdr_output: HdrOutput,
+    pub hdr_exposure: Exposure,
+    // output, zoom video frames streamed into one file or pipe, frames are then not saved as images
+    pub frame_sink: FrameSink,
+    // output, save zoom video frames as images also with frame sink
+    pub save_frames: bool,
+    // output, nebula images larger than memory rendered in tiles and stitched
+    pub tiling: Tiling,
     // area config
     pub width_xl: usize,  // length [-0.5, 0.5] = 2 intervals for width_re = 1
     pub width_xp: usize,  // points [-0.5, 0, 0.5] = 3 elements for width_re = 1
@@ -35,6 +83,49 @@ pub struct FractalConfig {
     pub update_min: u64,
 }
 
+/**
+ * Default of each config field
+ * Config constructors set the fractal, calculation and area config, optional features come from here
+ */
+pub const DEFAULT_CONFIG: FractalConfig = FractalConfig {
+    name: "",
+    orbits: Ignore,
+    fractal_calc_type: StaticImageMandelbrot,
+    iteration_min: 0,
+    iteration_max: 0,
+    resolution_multiplier: Single,
+    palette: Nothing,
+    palette_zero: Nothing,
+    palette_space: Srgb,
+    palette_shift: 0.0,
+    coloring_mode: Perfect,
+    color_cycle_frames: 0,
+    coloring_time_constant: 0.0,
+    spectral_channels: Cow::Borrowed(EULER_CHANNELS),
+    orbit_trap: NoTrap,
+    orbit_trap_coloring: Instead,
+    supersampling: Supersampling::Nothing,
+    output_files: DEFAULT_OUTPUT_FILES,
+    save_images: true,
+    min_save_width: 0,
+    preview_width: 0,
+    density_output: DensityOutput::Nothing,
+    hdr_output: HdrOutput::Nothing,
+    hdr_exposure: NORMALIZED_MAX,
+    frame_sink: FrameSink::Nothing,
+    save_frames: false,
+    tiling: Tiling::Nothing,
+    width_xl: 0,
+    width_xp: 1,
+    height_yl: 0,
+    height_yp: 1,
+    width_re: 1.0,
+    center_re: 0.0,
+    center_im: 0.0,
+    update_max: 1,
+    update_min: 0,
+};
+
 impl FractalConfig {
     pub fn is_dynamic(&self) -> bool {
         self.fractal_calc_type == DynamicSequenceNebula
@@ -44,6 +135,112 @@ impl FractalConfig {
         self.fractal_calc_type == StaticImageMandelbrot
             || self.fractal_calc_type == StaticSequenceMandelbrot
     }
+
+    // Optional features, not in the config structs of config.rs, each tagged by the request of its field
+    // let config = MandelbrotImage { ... }.init().coloring(ColoringMode::Smooth).supersampling(Grid(Square3));
+
+    // user-030, coloring mode of Mandelbrot like fractals
+    pub fn coloring(mut self, coloring_mode: ColoringMode) -> Self {
+        self.coloring_mode = coloring_mode;
+        self
+    }
+
+    // user-032, color space of palette interpolation
+    pub fn palette_space(mut self, palette_space: ColorSpace) -> Self {
+        self.palette_space = palette_space;
+        self
+    }
+
+    // user-044, palettes rotated by fraction of their length
+    pub fn palette_shift(mut self, shift: f64) -> Self {
+        self.palette_shift = shift;
+        self
+    }
+
+    // user-035, static images, number of frames of palette rotation
+    pub fn color_cycle(mut self, frames: u64) -> Self {
+        self.color_cycle_frames = frames;
+        self
+    }
+
+    // user-036, zoom videos, number of frames to follow the change of value distribution
+    pub fn coloring_time_constant(mut self, frames: f64) -> Self {
+        self.coloring_time_constant = frames;
+        self
+    }
+
+    // user-029, orbit trap of Mandelbrot like fractals
+    pub fn orbit_trap(mut self, orbit_trap: OrbitTrap, coloring: OrbitTrapColoring) -> Self {
+        self.orbit_trap = orbit_trap;
+        self.orbit_trap_coloring = coloring;
+        self
+    }
+
+    // user-048, anti-aliasing of Mandelbrot like fractals, adaptive by user-049
+    pub fn supersampling(mut self, supersampling: Supersampling) -> Self {
+        self.supersampling = supersampling;
+        self
+    }
+
+    // user-042, directory, file name template and format of colored image
+    pub fn output_files(mut self, output_files: OutputFiles) -> Self {
+        self.output_files = output_files;
+        self
+    }
+
+    // user-043, full size results are not saved if off or if narrower than min_save_width
+    pub fn save_images(mut self, save_images: bool, min_save_width: usize) -> Self {
+        self.save_images = save_images;
+        self.min_save_width = min_save_width;
+        self
+    }
+
+    // user-043, width of preview image saved always
+    pub fn preview(mut self, width: usize) -> Self {
+        self.preview_width = width;
+        self
+    }
+
+    // user-040, raw pixel values for external coloring
+    pub fn density_output(mut self, density_output: DensityOutput) -> Self {
+        self.density_output = density_output;
+        self
+    }
+
+    // user-041, floating point density and colors
+    pub fn hdr_output(mut self, hdr_output: HdrOutput, exposure: Exposure) -> Self {
+        self.hdr_output = hdr_output;
+        self.hdr_exposure = exposure;
+        self
+    }
+
+    /**
+     * user-046, zoom video frames streamed into one file or pipe, instead of images saved for each frame.
+     * For path "-" the frames take over the standard output, file descriptor 1 is then redirected
+     * to the standard error, so println! of the whole process goes there.
+     */
+    pub fn frame_sink(mut self, frame_sink: FrameSink) -> Self {
+        self.frame_sink = frame_sink;
+        self
+    }
+
+    // user-046, zoom videos with frame sink, save also each frame as image
+    pub fn save_frames(mut self) -> Self {
+        self.save_frames = true;
+        self
+    }
+
+    // user-050, nebula images larger than memory
+    pub fn tiling(mut self, tiling: Tiling) -> Self {
+        self.tiling = tiling;
+        self
+    }
+
+    // user-044, spectral channels read from image metadata
+    pub fn spectral_channels(mut self, channels: Vec<Channel>) -> Self {
+        self.spectral_channels = Cow::Owned(channels);
+        self
+    }
 }
 
 pub struct Optimizer {
@@ -93,7 +290,7 @@ impl Optimizer {
 /**
 - Orbit types for nebula fractals
 */
-#[derive(PartialEq, Clone, Copy)]
+#[derive(PartialEq, Clone, Copy, Debug)]
 pub enum OrbitType {
     // Ignore orbits for Mandelbrot like calculations
     Ignore,
@@ -112,9 +309,9 @@ pub enum OrbitType {
  * - for each calculation, count domain elements matching the intermediate-calculation results
  *
  * Euler fractal
- * - split primes, Fibonacci's and other calculation sequences to RGB spectra
+ * - split primes, Fibonacci's and other calculation sequences to spectral channels
  */
-#[derive(PartialEq, Clone, Copy)]
+#[derive(PartialEq, Clone, Copy, Debug)]
 pub enum FractalCalculationType {
     /** Nebula fractals
      * - drop calculation path to px grid immediately
@@ -134,13 +331,29 @@ pub enum FractalCalculationType {
     StaticSequenceMandelbrot,
     /**
      * Euler like fractals
-     * - wip
+     * - path elements split to configured spectral channels, mixed to RGB by their colors
      */
     StaticSpectralImageEuler,
 }
 
 pub trait FractalMath<M>: Sync + Send {
     fn math(&self, m: &mut M, origin_re: f64, origin_im: f64);
+
+    /**
+     * Degree of the map, z^2 + c by default
+     * used for smooth coloring of Mandelbrot like fractals
+     */
+    fn degree(&self) -> f64 {
+        2.0
+    }
+
+    /**
+     * Text of fractals defined at runtime, saved in image metadata to render the image again
+     * None for fractals defined by Rust code
+     */
+    fn definition(&self) -> Option<String> {
+        None
+    }
 }
 
 pub trait MemType<M>: Sync + Send {
@@ -179,9 +392,6 @@ pub fn init_trivial_static_config() -> FractalConfig {
         iteration_max: 3, // path length too short = 0 & 1, convergent = 2, divergent = 3
         resolution_multiplier: Single,
 
-        palette: Nothing,
-        palette_zero: Nothing,
-
         // same as stupid Cartesian coordinates
         // [0.0, 0.0] at the center
         width_xl: 2,
@@ -192,8 +402,7 @@ pub fn init_trivial_static_config() -> FractalConfig {
         center_re: 0.0,
         center_im: 0.0,
 
-        update_max: 1,
-        update_min: 0,
+        ..DEFAULT_CONFIG
     }
 }
 
@@ -205,9 +414,6 @@ pub const fn init_trivial_dynamic_config(domain_side_points: usize) -> FractalCo
         iteration_max: 3, // path length too short = 0,1, convergent = 2, divergent = 3
         resolution_multiplier: Single,
 
-        palette: Nothing,
-        palette_zero: Nothing,
-
         // same as stupid Cartesian coordinates
         // [0.0, 0.0] at the center
         width_xl: domain_side_points - 1,
@@ -219,16 +425,20 @@ pub const fn init_trivial_dynamic_config(domain_side_points: usize) -> FractalCo
         center_im: 0.0,
 
         orbits: Finite,
-        update_max: 1,
-        update_min: 0,
+        ..DEFAULT_CONFIG
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::fractal::{init_trivial_fractal, FractalMath, MemType, Optimizer};
+    use crate::coloring_mode::ColoringMode::Smooth;
+    use crate::fractal::{init_trivial_fractal, init_trivial_static_config, FractalMath, MemType, Optimizer};
     use crate::mem::Mem;
+    use crate::orbit_trap::OrbitTrap::Point;
+    use crate::orbit_trap::OrbitTrapColoring::Alongside;
     use crate::pixel_states::DomainElementState::{ActiveNew, HibernatedDeepBlack};
+    use crate::resolution_multiplier::ResolutionMultiplier::Square3;
+    use crate::supersampling::Supersampling::Grid;
 
     #[test]
     fn test_math() {
@@ -252,4 +462,20 @@ mod tests {
         assert_eq!((o.initial_state_for)(0.0, 0.0), HibernatedDeepBlack);
         assert_eq!((o.initial_state_for)(1.0, 1.0), ActiveNew);
     }
+
+    #[test]
+    fn test_builder() {
+        let c = init_trivial_static_config()
+            .coloring(Smooth)
+            .orbit_trap(Point(0.0, 0.0), Alongside)
+            .supersampling(Grid(Square3))
+            .preview(5);
+
+        assert_eq!(c.coloring_mode, Smooth);
+        assert_eq!(c.orbit_trap, Point(0.0, 0.0));
+        assert_eq!(c.orbit_trap_coloring, Alongside);
+        assert_eq!(c.supersampling, Grid(Square3));
+        assert_eq!(c.preview_width, 5);
+        assert!(c.save_images);
+    }
 }
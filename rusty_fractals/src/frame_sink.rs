@@ -0,0 +1,435 @@
+use crate::data_image::DataImage;
+use crate::palette::Palette;
+use image::Rgb;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+/**
+ * Zoom video frames streamed into a single file or pipe, instead of numbered images
+ * path "-" is the standard output, log messages are then moved to the standard error
+ *
+ * cargo run | ffmpeg -i - zoom.mp4                                                for Y4m("-", 25)
+ * cargo run | ffmpeg -f rawvideo -pix_fmt rgb24 -s 1280x720 -r 25 -i - zoom.mp4   for RawRgb("-")
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FrameSink {
+    Nothing,
+    // uncompressed YUV4MPEG2 stream, path and frames per second
+    Y4m(&'static str, u32),
+    // rgb24 frames without any header
+    RawRgb(&'static str),
+    // colors quantized to the fractal palettes
+    Gif(Animation),
+    Apng(Animation),
+}
+
+/**
+ * Short animation, the file is finished after the given number of frames, later frames are not written
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Animation {
+    pub path: &'static str,
+    // at least 1
+    pub frames: u64,
+    // time of one frame in milliseconds, GIF needs at least 10
+    // GIF delay is quantized to hundredths of a second, 66 ms plays as 70 ms
+    pub delay_ms: u16,
+    // 0 for infinite loop
+    pub loops: u16,
+}
+
+pub struct FrameWriter {
+    target: Target,
+    width: usize,
+    height: usize,
+}
+
+enum Target {
+    Stream {
+        out: Box<dyn Write + Send + Sync>,
+        is_y4m: bool,
+    },
+    Gif {
+        encoder: gif::Encoder<BufWriter<File>>,
+        palette: Vec<Rgb<u8>>,
+        // color to palette index, cached nearest colors
+        indexes: HashMap<Rgb<u8>, u8>,
+        delay_cs: u16,
+        remaining: u64,
+    },
+    Apng {
+        writer: png::Writer<BufWriter<File>>,
+        remaining: u64,
+    },
+    Finished,
+}
+
+impl FrameWriter {
+    /**
+     * Append the colored image as the next frame
+     */
+    pub fn write_frame(&mut self, data_image: &DataImage, frame: u64) -> Result<(), String> {
+        let pixels: Vec<Rgb<u8>> = (0..self.height)
+            .flat_map(|y| (0..self.width).map(move |x| (x, y)))
+            .map(|(x, y)| data_image.color_at(x, y).unwrap_or(Rgb([0, 0, 0])))
+            .collect();
+        let (width, height) = (self.width as u16, self.height as u16);
+        let error = |e: &dyn std::fmt::Display| format!("frame {}: {}", frame, e);
+        match &mut self.target {
+            Target::Stream { out, is_y4m } => {
+                let mut bytes = Vec::with_capacity(pixels.len() * 3 + 6);
+                if *is_y4m {
+                    // planar 4:4:4, Y plane, then U plane, then V plane
+                    bytes.extend_from_slice(b"FRAME\n");
+                    let yuv: Vec<[u8; 3]> = pixels.iter().map(|p| rgb_to_yuv(*p)).collect();
+                    for plane in 0..3 {
+                        bytes.extend(yuv.iter().map(|c| c[plane]));
+                    }
+                } else {
+                    bytes.extend(pixels.iter().flat_map(|p| p.0));
+                }
+                out.write_all(&bytes).and_then(|_| out.flush()).map_err(|e| error(&e))
+            }
+            Target::Gif { encoder, palette, indexes, delay_cs, remaining } => {
+                let buffer: Vec<u8> = pixels
+                    .iter()
+                    .map(|p| *indexes.entry(*p).or_insert_with(|| nearest(palette, *p)))
+                    .collect();
+                let gif_frame = gif::Frame {
+                    width,
+                    height,
+                    delay: *delay_cs,
+                    buffer: buffer.into(),
+                    ..gif::Frame::default()
+                };
+                encoder.write_frame(&gif_frame).map_err(|e| error(&e))?;
+                *remaining -= 1;
+                if *remaining == 0 {
+                    // trailer is written when the encoder is dropped
+                    self.target = Target::Finished;
+                }
+                Ok(())
+            }
+            Target::Apng { writer, remaining } => {
+                let bytes: Vec<u8> = pixels.iter().flat_map(|p| p.0).collect();
+                writer.write_image_data(&bytes).map_err(|e| error(&e))?;
+                *remaining -= 1;
+                if *remaining == 0
+                    && let Target::Apng { writer, .. } = std::mem::replace(&mut self.target, Target::Finished)
+                {
+                    writer.finish().map_err(|e| error(&e))?;
+                }
+                Ok(())
+            }
+            Target::Finished => Ok(()),
+        }
+    }
+}
+
+/**
+ * None for FrameSink::Nothing
+ */
+pub fn open(
+    sink: FrameSink,
+    width: usize,
+    height: usize,
+    palette: &Palette,
+    palette_zero: &Palette,
+) -> Result<Option<FrameWriter>, String> {
+    let (path, is_y4m) = match sink {
+        FrameSink::Nothing => return Ok(None),
+        FrameSink::Y4m(path, _) => (path, true),
+        FrameSink::RawRgb(path) => (path, false),
+        FrameSink::Gif(animation) => {
+            let target = open_gif(animation, width, height, palette, palette_zero)
+                .map_err(|e| format!("{}: {}", animation.path, e))?;
+            return Ok(Some(FrameWriter { target, width, height }));
+        }
+        FrameSink::Apng(animation) => {
+            let target = open_apng(animation, width, height).map_err(|e| format!("{}: {}", animation.path, e))?;
+            return Ok(Some(FrameWriter { target, width, height }));
+        }
+    };
+    let mut out: Box<dyn Write + Send + Sync> = if path == "-" {
+        Box::new(BufWriter::new(stdout_for_frames()?))
+    } else {
+        Box::new(BufWriter::new(File::create(path).map_err(|e| format!("{}: {}", path, e))?))
+    };
+    if let FrameSink::Y4m(_, fps) = sink {
+        // C444 keeps full color resolution, A1:1 square pixels
+        writeln!(out, "YUV4MPEG2 W{} H{} F{}:1 Ip A1:1 C444", width, height, fps)
+            .map_err(|e| format!("{}: {}", path, e))?;
+    }
+    Ok(Some(FrameWriter {
+        target: Target::Stream { out, is_y4m },
+        width,
+        height,
+    }))
+}
+
+fn open_gif(
+    animation: Animation,
+    width: usize,
+    height: usize,
+    palette: &Palette,
+    palette_zero: &Palette,
+) -> Result<Target, String> {
+    check_frames(animation)?;
+    // GIF counts the delay in hundredths of a second
+    if animation.delay_ms < 10 {
+        return Err(format!("frame delay {} ms is shorter than 10 ms", animation.delay_ms));
+    }
+    let (w, h) = gif_size(width, height)?;
+    let palette = gif_palette(palette, palette_zero);
+    let global_palette: Vec<u8> = palette.iter().flat_map(|c| c.0).collect();
+    let file = File::create(animation.path).map_err(|e| e.to_string())?;
+    let mut encoder = gif::Encoder::new(BufWriter::new(file), w, h, &global_palette).map_err(|e| e.to_string())?;
+    let repeat = match animation.loops {
+        0 => gif::Repeat::Infinite,
+        n => gif::Repeat::Finite(n),
+    };
+    encoder.set_repeat(repeat).map_err(|e| e.to_string())?;
+    Ok(Target::Gif {
+        encoder,
+        palette,
+        indexes: HashMap::new(),
+        // nearest hundredth of a second
+        delay_cs: ((animation.delay_ms as u32 + 5) / 10) as u16,
+        remaining: animation.frames,
+    })
+}
+
+fn open_apng(animation: Animation, width: usize, height: usize) -> Result<Target, String> {
+    check_frames(animation)?;
+    let file = File::create(animation.path).map_err(|e| e.to_string())?;
+    let mut encoder = png::Encoder::new(BufWriter::new(file), width as u32, height as u32);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    let frames = u32::try_from(animation.frames).map_err(|e| e.to_string())?;
+    encoder
+        .set_animated(frames, animation.loops as u32)
+        .and_then(|_| encoder.set_frame_delay(animation.delay_ms, 1000))
+        .map_err(|e| e.to_string())?;
+    let writer = encoder.write_header().map_err(|e| e.to_string())?;
+    Ok(Target::Apng {
+        writer,
+        remaining: animation.frames,
+    })
+}
+
+fn check_frames(animation: Animation) -> Result<(), String> {
+    if animation.frames == 0 {
+        return Err("animation needs at least one frame".to_string());
+    }
+    Ok(())
+}
+
+fn gif_size(width: usize, height: usize) -> Result<(u16, u16), String> {
+    match (u16::try_from(width), u16::try_from(height)) {
+        (Ok(w), Ok(h)) => Ok((w, h)),
+        _ => Err(format!("{}x{} is too large for GIF", width, height)),
+    }
+}
+
+/**
+ * At most 256 colors of both palettes and black, evenly sampled from longer spectra
+ */
+fn gif_palette(palette: &Palette, palette_zero: &Palette) -> Vec<Rgb<u8>> {
+    let mut colors: Vec<Rgb<u8>> = Vec::new();
+    for c in palette.spectrum.iter().chain(&palette_zero.spectrum) {
+        if !colors.contains(c) {
+            colors.push(*c);
+        }
+    }
+    let black = Rgb([0, 0, 0]);
+    colors.retain(|c| *c != black);
+    if colors.len() > 255 {
+        let n = colors.len();
+        colors = (0..255).map(|i| colors[i * (n - 1) / 254]).collect();
+    }
+    colors.insert(0, black);
+    colors
+}
+
+fn nearest(palette: &[Rgb<u8>], color: Rgb<u8>) -> u8 {
+    let distance = |c: &Rgb<u8>| -> i32 { (0..3).map(|i| (c[i] as i32 - color[i] as i32).pow(2)).sum() };
+    palette
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, c)| distance(c))
+        .map(|(i, _)| i as u8)
+        .unwrap_or(0)
+}
+
+/**
+ * Frames keep the original standard output, println! continues to the standard error
+ */
+#[cfg(unix)]
+fn stdout_for_frames() -> Result<File, String> {
+    use std::os::fd::FromRawFd;
+    std::io::stdout().flush().map_err(|e| e.to_string())?;
+    // SAFETY: duplicated descriptor is owned only by the returned File
+    unsafe {
+        let fd = libc::dup(libc::STDOUT_FILENO);
+        if fd < 0 || libc::dup2(libc::STDERR_FILENO, libc::STDOUT_FILENO) < 0 {
+            return Err(std::io::Error::last_os_error().to_string());
+        }
+        Ok(File::from_raw_fd(fd))
+    }
+}
+
+#[cfg(not(unix))]
+fn stdout_for_frames() -> Result<File, String> {
+    Err("frames to standard output are supported only on unix, use a file path".to_string())
+}
+
+/**
+ * BT.601 limited range, Y in 16..=235, U and V in 16..=240
+ */
+fn rgb_to_yuv(c: Rgb<u8>) -> [u8; 3] {
+    let [r, g, b] = c.0.map(|v| v as i32);
+    let y = (66 * r + 129 * g + 25 * b + 128) >> 8;
+    let u = (-38 * r - 74 * g + 112 * b + 128) >> 8;
+    let v = (112 * r - 94 * g - 18 * b + 128) >> 8;
+    [(y + 16) as u8, (u + 128) as u8, (v + 128) as u8]
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fractal::init_trivial_dynamic_config;
+    use crate::frame_sink::{gif_palette, nearest, open, rgb_to_yuv, Animation, FrameSink};
+    use crate::palette::{init_trivial, Palette};
+    use crate::{area, data_image};
+    use image::Rgb;
+
+    #[test]
+    fn test_rgb_to_yuv() {
+        assert_eq!(rgb_to_yuv(Rgb([0, 0, 0])), [16, 128, 128]);
+        assert_eq!(rgb_to_yuv(Rgb([255, 255, 255])), [235, 128, 128]);
+        assert_eq!(rgb_to_yuv(Rgb([255, 0, 0])), [82, 90, 240]);
+    }
+
+    #[test]
+    fn test_open_nothing() {
+        assert!(open(FrameSink::Nothing, 4, 4, &init_trivial(), &init_trivial()).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_write_frame_y4m() {
+        let c = init_trivial_dynamic_config(3);
+        let di = data_image::init(&c, &area::init(&c));
+        di.color(1, 0, Rgb([255, 255, 255]));
+
+        let mut writer = open(FrameSink::Y4m("test_frames.y4m", 30), 2, 2, &init_trivial(), &init_trivial()).unwrap().unwrap();
+        writer.write_frame(&di, 1).unwrap();
+        writer.write_frame(&di, 2).unwrap();
+        drop(writer);
+
+        let bytes = std::fs::read("test_frames.y4m").unwrap();
+        std::fs::remove_file("test_frames.y4m").unwrap();
+        let header = b"YUV4MPEG2 W2 H2 F30:1 Ip A1:1 C444\n";
+        assert!(bytes.starts_with(header));
+        assert_eq!(bytes.len(), header.len() + 2 * (6 + 3 * 4));
+        let frame = &bytes[header.len()..header.len() + 18];
+        assert_eq!(&frame[..6], b"FRAME\n");
+        // Y plane, then U and V
+        assert_eq!(&frame[6..10], &[16, 235, 16, 16]);
+        assert_eq!(&frame[10..18], &[128; 8]);
+    }
+
+    #[test]
+    fn test_write_frame_raw() {
+        let c = init_trivial_dynamic_config(3);
+        let di = data_image::init(&c, &area::init(&c));
+        di.color(0, 1, Rgb([1, 2, 3]));
+
+        let mut writer = open(FrameSink::RawRgb("test_frames.rgb"), 2, 2, &init_trivial(), &init_trivial()).unwrap().unwrap();
+        writer.write_frame(&di, 1).unwrap();
+        drop(writer);
+
+        let bytes = std::fs::read("test_frames.rgb").unwrap();
+        std::fs::remove_file("test_frames.rgb").unwrap();
+        assert_eq!(bytes, vec![0, 0, 0, 0, 0, 0, 1, 2, 3, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_open_animation_invalid() {
+        let animation = Animation { path: "test_frames_invalid.gif", frames: 0, delay_ms: 100, loops: 0 };
+        let p = init_trivial();
+
+        assert!(open(FrameSink::Gif(animation), 2, 2, &p, &p).is_err());
+        assert!(open(FrameSink::Apng(animation), 2, 2, &p, &p).is_err());
+        let animation = Animation { frames: 2, delay_ms: 5, ..animation };
+        assert!(open(FrameSink::Gif(animation), 2, 2, &p, &p).is_err());
+        // nothing was created
+        assert!(!std::path::Path::new("test_frames_invalid.gif").exists());
+    }
+
+    #[test]
+    fn test_write_frame_gif() {
+        let c = init_trivial_dynamic_config(3);
+        let di = data_image::init(&c, &area::init(&c));
+        di.color(1, 1, Rgb([0, 250, 0]));
+        let animation = Animation { path: "test_frames.gif", frames: 2, delay_ms: 66, loops: 0 };
+
+        let mut writer = open(FrameSink::Gif(animation), 2, 2, &init_trivial(), &init_trivial()).unwrap().unwrap();
+        for frame in 1..=3 {
+            writer.write_frame(&di, frame).unwrap();
+        }
+        drop(writer);
+
+        let file = std::fs::File::open("test_frames.gif").unwrap();
+        let mut decoder = gif::DecodeOptions::new().read_info(std::io::BufReader::new(file)).unwrap();
+        let mut frames = Vec::new();
+        while let Some(frame) = decoder.read_next_frame().unwrap() {
+            frames.push((frame.delay, frame.buffer.to_vec()));
+        }
+        std::fs::remove_file("test_frames.gif").unwrap();
+        // the third frame is after the end of animation
+        assert_eq!(frames.len(), 2);
+        // green is the third color of trivial palette, after black, 66 ms rounded to 7 cs
+        assert_eq!(frames[0], (7, vec![0, 0, 0, 2]));
+    }
+
+    #[test]
+    fn test_write_frame_apng() {
+        let c = init_trivial_dynamic_config(3);
+        let di = data_image::init(&c, &area::init(&c));
+        let animation = Animation { path: "test_frames.png", frames: 2, delay_ms: 40, loops: 3 };
+
+        let mut writer = open(FrameSink::Apng(animation), 2, 2, &init_trivial(), &init_trivial()).unwrap().unwrap();
+        writer.write_frame(&di, 1).unwrap();
+        writer.write_frame(&di, 2).unwrap();
+        drop(writer);
+
+        let file = std::fs::File::open("test_frames.png").unwrap();
+        let reader = png::Decoder::new(std::io::BufReader::new(file)).read_info().unwrap();
+        let control = reader.info().animation_control.unwrap();
+        std::fs::remove_file("test_frames.png").unwrap();
+        assert_eq!((control.num_frames, control.num_plays), (2, 3));
+    }
+
+    #[test]
+    fn test_gif_palette() {
+        let long = Palette {
+            spectrum: (0..=255).flat_map(|v| [Rgb([v, 0, 0]), Rgb([0, v, 0])]).collect(),
+        };
+
+        let palette = gif_palette(&init_trivial(), &init_trivial());
+        assert_eq!(palette, vec![Rgb([0, 0, 0]), Rgb([255, 0, 0]), Rgb([0, 255, 0]), Rgb([0, 0, 255])]);
+
+        let palette = gif_palette(&long, &init_trivial());
+        assert_eq!(palette.len(), 256);
+        assert_eq!(palette[0], Rgb([0, 0, 0]));
+        assert_eq!(palette[255], Rgb([0, 0, 255]));
+    }
+
+    #[test]
+    fn test_nearest() {
+        let palette = [Rgb([0, 0, 0]), Rgb([255, 0, 0]), Rgb([0, 255, 0])];
+
+        assert_eq!(nearest(&palette, Rgb([200, 30, 0])), 1);
+        assert_eq!(nearest(&palette, Rgb([10, 10, 10])), 0);
+    }
+}
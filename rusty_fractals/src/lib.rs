@@ -2,28 +2,45 @@ extern crate core;
 
 pub mod application;
 pub mod area;
+pub mod coloring_mode;
+pub mod color_space;
 pub mod constants;
+pub mod cyclic_color_distribution;
 pub mod data_image;
 pub mod data_px;
 pub mod data_px3;
 pub mod files;
+pub mod formula;
 pub mod fractal;
 pub mod fractal_log;
 pub mod fractal_stats;
+pub mod frame_sink;
 pub mod machine;
+pub mod mapped_color_distribution;
 pub mod mathematician;
 pub mod mem;
 pub mod mem_collatz;
 pub mod mem_phoenix;
+pub mod mem_zeta;
+pub mod metadata;
+pub mod orbit_trap;
 pub mod palette;
+pub mod palette_builder;
+pub mod palette_file;
 pub mod palette_utils;
 pub mod palettes;
 pub mod perfect_color_distribution;
 pub mod perfect_color_distribution_euler;
 pub mod perfect_color_distribution_nebula;
+pub mod perfect_color_distribution_orbit_trap;
 pub mod pixel_states;
+pub mod rerender;
 pub mod resolution_multiplier;
+pub mod smooth_color_distribution;
+pub mod supersampling;
+pub mod temporal_color_distribution;
+pub mod tiles;
 pub mod euler;
-pub mod pixel;
+pub mod polynomial;
 pub mod config;
 pub mod data;
@@ -1,24 +1,42 @@
 use crate::application::Application;
 use crate::area::Area;
+use crate::color_space::ColorSpace;
+use crate::coloring_mode::ColoringMode;
 use crate::constants::CALCULATION_BOUNDARY;
+use crate::cyclic_color_distribution::cyclically_color_mandelbrot_values;
 use crate::data_image::DataImage;
-use crate::files::save_image;
+use crate::data_px::PxValues;
+use crate::euler;
+use crate::files::{
+    save_density, save_hdr, save_image, save_preview, DensityOutput, Exposure, HdrOutput, OutputFiles,
+};
 use crate::fractal::FractalCalculationType::StaticImageNebula;
 use crate::fractal::{
     init_trivial_dynamic_config, init_trivial_static_config, FractalCalculationType, FractalConfig, FractalMath, MemType,
     Optimizer, OrbitType, TrivialFractal,
 };
 use crate::fractal_stats::Stats;
+use crate::mapped_color_distribution::{color_mandelbrot_values_mapped, color_nebula_values_mapped};
 use crate::mem::Mem;
+use crate::frame_sink;
+use crate::frame_sink::{FrameSink, FrameWriter};
+use crate::metadata;
+use crate::metadata::Metadata;
+use crate::orbit_trap::{OrbitTrap, OrbitTrapColoring, NO_TRAP};
 use crate::palette::Palette;
-use crate::palettes::new_palette_by_name;
+use crate::palettes::{load_palette_by_name_in_space, PaletteName};
 use crate::perfect_color_distribution::perfectly_color_mandelbrot_values;
 use crate::perfect_color_distribution_euler::perfectly_color_euler_values;
 use crate::perfect_color_distribution_nebula::perfectly_color_nebula_values;
+use crate::perfect_color_distribution_orbit_trap::perfectly_color_orbit_trap_values;
 use crate::pixel_states::DomainElementState;
 use crate::pixel_states::DomainElementState::{FinishedSuccess, FinishedTooLong, FinishedTooShort};
 use crate::resolution_multiplier::ResolutionMultiplier;
-use crate::{area, data_image, fractal, fractal_stats, pixel_states};
+use crate::smooth_color_distribution::{smooth_iteration, smoothly_color_mandelbrot_values};
+use crate::supersampling::{needs_refinement, sample_points, ColorSum, Supersampling, Supersamples};
+use crate::temporal_color_distribution::TemporalColoring;
+use crate::{area, data_image, fractal, fractal_stats, pixel_states, temporal_color_distribution};
+use image::Rgb;
 use rand::rng;
 use rand::seq::SliceRandom;
 use rayon::prelude::*;
@@ -59,6 +77,32 @@ where
     // mandelbrot specific
     // used to color the (black) inside of Mandelbrot set
     pub palette_zero: Palette,
+    // palette names, for metadata of saved images
+    pub palette_name: PaletteName,
+    pub palette_zero_name: PaletteName,
+    pub palette_space: ColorSpace,
+    pub palette_shift: f64,
+    pub coloring_mode: ColoringMode,
+    pub color_cycle_frames: u64,
+    // value to color mapping of previous zoom frames
+    pub temporal_coloring: RwLock<TemporalColoring>,
+    pub orbit_trap: OrbitTrap,
+    pub orbit_trap_coloring: OrbitTrapColoring,
+    pub supersampling: Supersampling,
+    // sub-samples of the static image, only with color cycling
+    supersamples: RwLock<Supersamples>,
+    // output config
+    pub output_files: OutputFiles,
+    pub save_images: bool,
+    pub min_save_width: usize,
+    pub preview_width: usize,
+    pub density_output: DensityOutput,
+    pub hdr_output: HdrOutput,
+    pub hdr_exposure: Exposure,
+    pub frame_sink: FrameSink,
+    pub save_frames: bool,
+    // opened with the first zoom frame
+    frame_writer: RwLock<Option<FrameWriter>>,
     // calculation config
     pub orbits: OrbitType, // fractal::finite_orbits / infinite_orbits
     pub iteration_min: u64,
@@ -79,7 +123,10 @@ where
     last_partial_refresh: RwLock<Instant>,
 }
 
-pub fn init<F, M>(config: &FractalConfig, fractal: F) -> Machine<'static, F, M>
+/**
+ * Error if a palette file can't be loaded or coloring mode is invalid, before anything is calculated
+ */
+pub fn init<F, M>(config: &FractalConfig, fractal: F) -> Result<Machine<'static, F, M>, String>
 where
     F: FractalMath<M>,
     M: MemType<M>,
@@ -91,13 +138,16 @@ pub fn init_o<F, M>(
     config: &FractalConfig,
     fractal: F,
     oo: Option<Optimizer>,
-) -> Machine<'static, F, M>
+) -> Result<Machine<'static, F, M>, String>
 where
     F: FractalMath<M>,
     M: MemType<M>,
 {
+    config.coloring_mode.validate()?;
     let area: Area = area::init(config);
-    Machine {
+    let palette = load_palette_by_name_in_space(&config.palette, config.palette_space)?;
+    let palette_zero = load_palette_by_name_in_space(&config.palette_zero, config.palette_space)?;
+    Ok(Machine {
         fractal,
         name: config.name,
         fractal_calc_type: config.fractal_calc_type,
@@ -114,8 +164,31 @@ where
         // nebula fractals include only calculations longer then min
         iteration_min: config.iteration_min,
         iteration_max: config.iteration_max,
-        palette: new_palette_by_name(&config.palette),
-        palette_zero: new_palette_by_name(&config.palette_zero),
+        palette: palette.rotated(config.palette_shift),
+        palette_zero: palette_zero.rotated(config.palette_shift),
+        palette_name: config.palette.clone(),
+        palette_zero_name: config.palette_zero.clone(),
+        palette_space: config.palette_space,
+        palette_shift: config.palette_shift,
+        coloring_mode: config.coloring_mode,
+        color_cycle_frames: config.color_cycle_frames,
+        temporal_coloring: RwLock::new(temporal_color_distribution::init(
+            config.coloring_time_constant,
+        )),
+        orbit_trap: config.orbit_trap,
+        orbit_trap_coloring: config.orbit_trap_coloring,
+        supersampling: config.supersampling,
+        supersamples: RwLock::new(Supersamples::default()),
+        output_files: config.output_files,
+        save_images: config.save_images,
+        min_save_width: config.min_save_width,
+        preview_width: config.preview_width,
+        density_output: config.density_output,
+        hdr_output: config.hdr_output,
+        hdr_exposure: config.hdr_exposure,
+        frame_sink: config.frame_sink,
+        save_frames: config.save_frames,
+        frame_writer: RwLock::new(None),
 
         resolution_multiplier: config.resolution_multiplier,
         orbits: OrbitType::Finite,
@@ -126,21 +199,21 @@ where
         app_ref: None,
         // machine values
         last_partial_refresh: RwLock::new(Instant::now()),
-    }
+    })
 }
 
 pub fn init_trivial_static() -> Machine<'static, TrivialFractal, Mem> {
     let co = init_trivial_static_config();
     let fr = fractal::init_trivial_fractal();
 
-    init(&co, fr)
+    init(&co, fr).unwrap()
 }
 
 pub fn init_trivial_dynamic() -> Machine<'static, TrivialFractal, Mem> {
     let conf = init_trivial_dynamic_config(3);
     let fractal = fractal::init_trivial_fractal();
 
-    init(&conf, fractal)
+    init(&conf, fractal).unwrap()
 }
 
 impl<'lt, F, M> Machine<'lt, F, M>
@@ -156,11 +229,17 @@ where
         println!("trigger_calculation()");
 
         let fractal_calculation = self.fractal_calc_type;
+        if matches!(fractal_calculation, StaticImageNebula | DynamicSequenceNebula)
+            && self.coloring_mode.uses_smooth_iteration()
+        {
+            println!("{:?} coloring isn't available for nebula, using perfect coloring", self.coloring_mode);
+        }
 
         match fractal_calculation {
             StaticImageNebula => {
                 // Fine fractal image
                 self.calculate_nebula_static_image();
+                self.color_cycle();
             }
             DynamicSequenceNebula => {
                 // Fine fractal video
@@ -168,7 +247,9 @@ where
             }
             StaticImageMandelbrot => {
                 // Hard fractal image
-                self.calculate_mandelbrot(0);
+                self.calculate_mandelbrot();
+                self.save_results(0);
+                self.color_cycle();
             }
             StaticSequenceMandelbrot => {
                 // Hard fractal video
@@ -217,11 +298,11 @@ where
                 self.paint_partial_calculation_results_states_with_paths(); // only every 100+ ms
             });
         }
-        perfectly_color_nebula_values(&self.data_image, &self.palette);
+        self.color_nebula_values(&self.palette);
 
         self.paint_final_calculation_result_colors();
 
-        save_image(&self.data_image, self.name, 0);
+        self.save_results(0);
     }
 
     /**
@@ -265,7 +346,7 @@ where
         self.clear_all_px_data();
         self.translate_all_paths_to_point_grid();
 
-        perfectly_color_nebula_values(&self.data_image, &self.palette);
+        self.color_nebula_values(&self.palette);
 
         self.paint_final_calculation_result_colors();
     }
@@ -280,9 +361,11 @@ where
     pub fn calculate_euler(&self) {
         println!("calculate_euler()");
 
+        euler::init_sequences(&self.data_image.spectral_channels, self.iteration_max);
+
         let coordinates_xy = shuffled_calculation_coordinates();
+        // each path element is added to the first matching of the configured spectral channels
         coordinates_xy.par_iter().for_each(|xy| {
-            // TODO
             self.chunk_calculation(&xy);
             self.paint_partial_calculation_results_states_maybe();
         });
@@ -294,7 +377,7 @@ where
 
         self.paint_final_calculation_result_colors();
 
-        save_image(&self.data_image, self.name, 0);
+        self.save_results(0);
     }
 
     // in sequence executes as 20x20 parallel for each image part/chunk
@@ -437,6 +520,19 @@ where
     }
 
     pub fn calculate_path(&self, origin_re: f64, origin_im: f64) -> (u64, u64) {
+        let (iterator, length) = self.path_length(origin_re, origin_im, &self.area);
+        if self.path_test(length, iterator) {
+            // This origin produced good data
+            self.record_path(origin_re, origin_im, iterator);
+        }
+        (iterator, length)
+    }
+
+    /**
+     * Amount of iterations and of path elements within the area
+     * The area is the whole image also for tiles of tiled images.
+     */
+    pub fn path_length(&self, origin_re: f64, origin_im: f64, area: &Area) -> (u64, u64) {
         let cb = CALCULATION_BOUNDARY as f64;
 
         let mut m = M::new(origin_re, origin_im);
@@ -449,44 +545,68 @@ where
             // Most of the long and expensive calculations end up inside Mandelbrot set, useless
             // It is 1.68x faster to calculate path twice, and to record exclusively the good paths
             self.fractal.math(&mut m, origin_re, origin_im);
-            if self.area.contains(m.re(), m.im()) {
+            if area.contains(m.re(), m.im()) {
                 // this becomes important for zoom, when only a small amount
                 // of calculation path elements is contained withing tiny area
                 length += 1;
             }
             iterator += 1;
         }
+        (iterator, length)
+    }
 
-        if self.path_test(length, iterator)
-        // This origin produced good data
-        // Record the calculation path
-        {
-            let mut m = M::new(origin_re, origin_im);
-
-            let mut path: Vec<[f64; 2]> = Vec::new();
-            for _ in 0..iterator {
-                self.fractal.math(&mut m, origin_re, origin_im);
-                if self.area.contains(m.re(), m.im()) {
-                    path.push([m.re(), m.im()]);
-                }
-            }
-
-            // if iteration_max increased, ignore possible extension of previous calculation paths
-            // path elements are going to migrate out of the screen very soon
-            // removed last_iteration, last_visited_re, last_visited_im
+    /**
+     * Record the calculation path of good origin, elements within the Machine area
+     */
+    pub fn record_path(&self, origin_re: f64, origin_im: f64, iterator: u64) {
+        let (path, iterations) = self.path_elements(origin_re, origin_im, iterator, &self.area);
+
+        // if iteration_max increased, ignore possible extension of previous calculation paths
+        // path elements are going to migrate out of the screen very soon
+        // removed last_iteration, last_visited_re, last_visited_im
+
+        self.stats.paths_new_points_amount_add(*&path.len());
+
+        // save path only for wrap calculation of static image, when data are static, so I can't just get the longest path
+        if self.data_image.is_dynamic() {
+            // move path to dynamic data
+            self.data_image.save_path(path);
+        } else if self.fractal_calc_type == StaticSpectralImageEuler {
+            self.data_image
+                .translate_one_path_to_spectra_now(path, iterations, &self.area);
+        } else {
+            self.data_image
+                .translate_one_path_to_point_grid_now(path, &self.area);
+        }
+    }
 
-            self.stats.paths_new_points_amount_add(*&path.len());
+    /**
+     * Path elements within the area, with their iteration numbers for Euler spectral channels
+     * The area is the whole image also for tiles of tiled images.
+     */
+    pub fn path_elements(
+        &self,
+        origin_re: f64,
+        origin_im: f64,
+        iterator: u64,
+        area: &Area,
+    ) -> (Vec<[f64; 2]>, Vec<u64>) {
+        let mut m = M::new(origin_re, origin_im);
 
-            // save path only for wrap calculation of static image, when data are static, so I can't just get the longest path
-            if self.data_image.is_dynamic() {
-                // move path to dynamic data
-                self.data_image.save_path(path);
-            } else {
-                self.data_image
-                    .translate_one_path_to_point_grid_now(path, &self.area);
+        let is_euler = self.fractal_calc_type == StaticSpectralImageEuler;
+        let mut path: Vec<[f64; 2]> = Vec::new();
+        // iteration numbers of path elements, classified by Euler spectral channels
+        let mut iterations: Vec<u64> = Vec::new();
+        for i in 0..iterator {
+            self.fractal.math(&mut m, origin_re, origin_im);
+            if area.contains(m.re(), m.im()) {
+                path.push([m.re(), m.im()]);
+                if is_euler {
+                    iterations.push(i + 1);
+                }
             }
         }
-        (iterator, length)
+        (path, iterations)
     }
 
     pub fn state_from_path_length(&self, iterator: u64, path_length: u64) -> DomainElementState {
@@ -514,7 +634,7 @@ where
             println!("{}:", it);
             self.calculate_nebula_dynamic_data();
 
-            save_image(&self.data_image, self.name, it);
+            self.save_frame(it);
 
             // prepare next frame
             self.zoom_in();
@@ -534,7 +654,8 @@ where
         println!("calculate_mandelbrot_zoom()");
         for it in 1.. {
             println!("{}:", it);
-            self.calculate_mandelbrot(it);
+            self.calculate_mandelbrot();
+            self.save_frame(it);
 
             // prepare next frame
             self.zoom_in();
@@ -554,7 +675,7 @@ where
     /**
      * Whole Mandelbrot calculation
      */
-    pub fn calculate_mandelbrot(&self, it: u64) {
+    pub fn calculate_mandelbrot(&self) {
         println!("calculate_mandelbrot()");
         let coordinates_xy: Vec<[u64; 2]> = shuffled_calculation_coordinates();
 
@@ -565,10 +686,334 @@ where
             self.paint_partial_calculation_results_states_maybe();
         });
         self.data_image.recalculate_pixels_states();
-        perfectly_color_mandelbrot_values(&self.data_image, &self.palette, &self.palette_zero);
+        self.color_mandelbrot_values(&self.palette, &self.palette_zero);
         self.paint_final_calculation_result_colors();
+    }
+
+    fn color_nebula_values(&self, palette: &Palette) {
+        if self.coloring_mode.is_mapped() {
+            color_nebula_values_mapped(&self.data_image, palette, self.coloring_mode);
+            return;
+        }
+        // zoom video, follow changes of value distribution slowly
+        let temporal = self.fractal_calc_type == DynamicSequenceNebula
+            && self.temporal_coloring.read().unwrap().is_on();
+        if temporal {
+            self.temporal_coloring
+                .write()
+                .unwrap()
+                .color_nebula_values(&self.data_image, palette);
+        } else {
+            perfectly_color_nebula_values(&self.data_image, palette);
+        }
+    }
+
+    fn color_mandelbrot_values(&self, palette: &Palette, palette_zero: &Palette) {
+        self.color_mandelbrot_pixels(palette, palette_zero, true);
+        self.calculate_supersamples(palette, palette_zero);
+    }
+
+    /**
+     * Color all pixels by the coloring mode, and by the orbit trap
+     * next_frame is false for sub-samples, which don't move temporal coloring of zoom videos
+     */
+    fn color_mandelbrot_pixels(&self, palette: &Palette, palette_zero: &Palette, next_frame: bool) {
+        // zoom video, follow changes of value distribution slowly
+        let temporal = self.fractal_calc_type == StaticSequenceMandelbrot
+            && self.temporal_coloring.read().unwrap().is_on();
+        match self.coloring_mode {
+            ColoringMode::Perfect if temporal && next_frame => self
+                .temporal_coloring
+                .write()
+                .unwrap()
+                .color_mandelbrot_values(&self.data_image, palette, palette_zero),
+            ColoringMode::Perfect if temporal => self
+                .temporal_coloring
+                .read()
+                .unwrap()
+                .recolor_mandelbrot_values(&self.data_image, palette, palette_zero),
+            ColoringMode::Perfect => {
+                perfectly_color_mandelbrot_values(&self.data_image, palette, palette_zero)
+            }
+            ColoringMode::Smooth => {
+                smoothly_color_mandelbrot_values(&self.data_image, palette, palette_zero)
+            }
+            ColoringMode::Cyclic(cycle) => {
+                cyclically_color_mandelbrot_values(&self.data_image, palette, palette_zero, cycle)
+            }
+            mode @ (ColoringMode::Linear
+            | ColoringMode::Log
+            | ColoringMode::Power(_)
+            | ColoringMode::Asinh) => {
+                color_mandelbrot_values_mapped(&self.data_image, palette, palette_zero, mode)
+            }
+        }
+        if self.orbit_trap.is_trap() {
+            perfectly_color_orbit_trap_values(
+                &self.data_image,
+                palette,
+                palette_zero,
+                self.orbit_trap_coloring,
+            );
+        }
+    }
+
+    /**
+     * Pixels to calculate sub-samples for, by pixel index x + y * width_xp
+     * Adaptive supersampling refines only pixels which differ from their neighbors.
+     */
+    fn refined_pixels(&self) -> Vec<usize> {
+        if !self.supersampling.is_on() {
+            return Vec::new();
+        }
+        (0..self.width_xp * self.height_yp)
+            .into_par_iter()
+            .filter(|i| match self.supersampling {
+                Supersampling::Adaptive(_, min_difference) => {
+                    needs_refinement(&self.data_image, i % self.width_xp, i / self.width_xp, min_difference)
+                }
+                _ => true,
+            })
+            .collect()
+    }
+
+    /**
+     * Anti-aliasing, sub-samples are calculated in layers, one sub-sample of each refined pixel at a time.
+     * Static images with color cycling keep the sub-sample values of all layers, for recolor_supersamples().
+     * Zoom videos calculate the sub-samples again for each frame, as each frame is a new calculation.
+     */
+    fn calculate_supersamples(&self, palette: &Palette, palette_zero: &Palette) {
+        let refined = self.refined_pixels();
+        let keep = self.color_cycle_frames > 0 && self.fractal_calc_type == StaticImageMandelbrot;
+        let data = &self.data_image;
+        let xy = |i: usize| (i % self.width_xp, i / self.width_xp);
+        let plank = self.area.plank();
+        let layers = if refined.is_empty() {
+            0
+        } else {
+            sample_points(data, 0.0, 0.0, self.supersampling, plank).len()
+        };
+        let mut kept = Vec::new();
+        self.color_supersample_layers(&refined, layers, palette, palette_zero, |layer| {
+            refined.par_iter().for_each(|i| {
+                let (x, y) = xy(*i);
+                let (origin_re, origin_im) = data.origin_at(x, y);
+                let [re, im] = sample_points(data, origin_re, origin_im, self.supersampling, plank)[layer];
+                self.calculate_pixel_mandelbrot(x, y, re, im);
+            });
+            if keep {
+                kept.push(refined.iter().map(|i| data.px_values_at(xy(*i).0, xy(*i).1)).collect());
+            }
+        });
+        if keep {
+            *self.supersamples.write().unwrap() = Supersamples { refined, layers: kept };
+        }
+    }
+
+    /**
+     * Color the kept sub-samples again, without recalculation
+     */
+    fn recolor_supersamples(&self, palette: &Palette, palette_zero: &Palette) {
+        let supersamples = self.supersamples.read().unwrap();
+        let Supersamples { refined, layers } = &*supersamples;
+        let xy = |i: usize| (i % self.width_xp, i / self.width_xp);
+        self.color_supersample_layers(refined, layers.len(), palette, palette_zero, |layer| {
+            for (i, values) in refined.iter().zip(&layers[layer]) {
+                self.data_image.set_px_values(xy(*i).0, xy(*i).1, *values);
+            }
+        });
+    }
+
+    /**
+     * Color of each refined pixel becomes the average of its color and colors of its sub-samples
+     * set_layer sets values of one sub-sample to each refined pixel, each layer is then colored
+     * as the whole image, by the coloring mode and orbit trap, only the sums of colors are kept.
+     * Pixel values are restored after the last layer.
+     */
+    fn color_supersample_layers(
+        &self,
+        refined: &[usize],
+        layers: usize,
+        palette: &Palette,
+        palette_zero: &Palette,
+        mut set_layer: impl FnMut(usize),
+    ) {
+        if refined.is_empty() {
+            return;
+        }
+        let data = &self.data_image;
+        let xy = |i: usize| (i % self.width_xp, i / self.width_xp);
+        println!("supersampling refined {} of {} pixels", refined.len(), self.width_xp * self.height_yp);
+
+        // colors of all pixels, layers color also the pixels which are not refined
+        let colors: Vec<Option<Rgb<u8>>> = (0..self.width_xp * self.height_yp)
+            .map(|i| data.color_at(xy(i).0, xy(i).1))
+            .collect();
+        let values: Vec<PxValues> = refined.iter().map(|i| data.px_values_at(xy(*i).0, xy(*i).1)).collect();
+        let mut sums: Vec<ColorSum> = vec![ColorSum::default(); refined.len()];
+        for (sum, i) in sums.iter_mut().zip(refined) {
+            if let Some(color) = colors[*i] {
+                sum.add(color);
+            }
+        }
+
+        for layer in 0..layers {
+            set_layer(layer);
+            self.color_mandelbrot_pixels(palette, palette_zero, false);
+            for (sum, i) in sums.iter_mut().zip(refined) {
+                if let Some(color) = data.color_at(xy(*i).0, xy(*i).1) {
+                    sum.add(color);
+                }
+            }
+        }
+
+        for (i, color) in colors.into_iter().enumerate() {
+            if let Some(color) = color {
+                data.color(xy(i).0, xy(i).1, color);
+            }
+        }
+        for ((i, pixel_values), sum) in refined.iter().zip(values).zip(sums) {
+            let (x, y) = xy(*i);
+            data.set_px_values(x, y, pixel_values);
+            if let Some(color) = sum.average() {
+                data.color(x, y, color);
+            }
+        }
+    }
+
+    /**
+     * Save colored image, and optionally its 16-bit PNG copy, raw pixel values and HDR output
+     * preview is saved always, if enabled
+     */
+    pub fn save_results(&self, index: u64) {
+        let files = &self.output_files;
+        if self.preview_width > 0
+            && let Err(e) = save_preview(&self.data_image, files, self.name, index, self.preview_width)
+        {
+            println!("save_preview() failed: {}", e);
+        }
+        if !self.saves_images() {
+            return;
+        }
+        let metadata = self.metadata(index);
+        if let Err(e) = save_image(&self.data_image, files, &metadata) {
+            println!("save_image() failed: {}", e);
+        }
+        if let Err(e) = save_density(&self.data_image, files, self.name, index, self.density_output) {
+            println!("save_density() failed: {}", e);
+        }
+        if let Err(e) = save_hdr(&self.data_image, files, self.name, index, self.hdr_output, self.hdr_exposure) {
+            println!("save_hdr() failed: {}", e);
+        }
+    }
+
+    /**
+     * Zoom frame streamed to the frame sink isn't saved as image too, unless save_frames is on
+     */
+    pub fn save_frame(&self, frame: u64) {
+        if self.frame_sink == FrameSink::Nothing || self.save_frames {
+            self.save_results(frame);
+        }
+        self.write_frame(frame);
+    }
+
+    /**
+     * Append the zoom frame to the frame sink, if configured
+     */
+    pub fn write_frame(&self, frame: u64) {
+        let mut writer = self.frame_writer.write().unwrap();
+        if writer.is_none() {
+            let (width, height) = (self.width_xl, self.height_yl);
+            match frame_sink::open(self.frame_sink, width, height, &self.palette, &self.palette_zero) {
+                Ok(w) => *writer = w,
+                Err(e) => println!("frame_sink::open() failed: {}", e),
+            }
+        }
+        if let Some(w) = writer.as_mut()
+            && let Err(e) = w.write_frame(&self.data_image, frame)
+        {
+            println!("write_frame() failed: {}", e);
+        }
+    }
+
+    /**
+     * Scene of the current frame, to render the saved image again
+     */
+    pub fn metadata(&self, frame: u64) -> Metadata {
+        Metadata {
+            name: self.name.to_string(),
+            fractal_calc_type: self.fractal_calc_type,
+            width_x: self.width_xl,
+            height_y: self.height_yl,
+            center_re: self.area.center_re(),
+            center_im: self.area.center_im(),
+            width_re: self.area.width_re(),
+            iteration_min: self.iteration_min,
+            iteration_max: self.iteration_max,
+            orbits: self.orbits,
+            resolution_multiplier: self.resolution_multiplier,
+            palette: self.palette_name.clone(),
+            palette_zero: self.palette_zero_name.clone(),
+            palette_space: self.palette_space,
+            palette_shift: self.palette_shift,
+            coloring_mode: self.coloring_mode,
+            orbit_trap: self.orbit_trap,
+            orbit_trap_coloring: self.orbit_trap_coloring,
+            supersampling: self.supersampling,
+            spectral_channels: self.data_image.spectral_channels.to_vec(),
+            fractal_definition: self.fractal.definition(),
+            frame,
+            render_time: metadata::now(),
+            version: metadata::VERSION.to_string(),
+        }
+    }
+
+    /**
+     * Full size results are saved only if enabled and the image is wide enough
+     */
+    fn saves_images(&self) -> bool {
+        if !self.save_images {
+            println!("save_images is off, results not saved");
+            return false;
+        }
+        if self.width_xl < self.min_save_width {
+            println!("width {} below min_save_width {}, results not saved", self.width_xl, self.min_save_width);
+            return false;
+        }
+        true
+    }
 
-        save_image(&self.data_image, self.name, it);
+    /**
+     * Color cycling animation
+     * Recolor the finished static image with palette shifted a bit more for each frame.
+     * No recalculation, the first frame is the already saved image, the last one loops back to it.
+     */
+    pub fn color_cycle(&self) {
+        let frames = self.color_cycle_frames;
+        if frames == 0 {
+            return;
+        }
+        println!("color_cycle() {} frames", frames);
+        for frame in 1..frames {
+            let shift = frame as f64 / frames as f64;
+            let palette = self.palette.rotated(shift);
+            let palette_zero = self.palette_zero.rotated(shift);
+            match self.fractal_calc_type {
+                StaticImageNebula => self.color_nebula_values(&palette),
+                StaticImageMandelbrot => {
+                    self.color_mandelbrot_pixels(&palette, &palette_zero, true);
+                    self.recolor_supersamples(&palette, &palette_zero);
+                }
+                _ => return,
+            }
+            let mut metadata = self.metadata(frame);
+            metadata.palette_shift = (self.palette_shift + shift).rem_euclid(1.0);
+            if self.saves_images()
+                && let Err(e) = save_image(&self.data_image, &self.output_files, &metadata)
+            {
+                println!("save_image() failed: {}", e);
+            }
+        }
     }
 
     fn chunk_calculation_mandelbrot(&self, xy: &[u64; 2]) {
@@ -579,36 +1024,46 @@ where
                 let (state, origin_re, origin_im) = self.data_image.state_origin_at(x, y);
 
                 if pixel_states::is_active_new(state) {
-                    /*
-                     * calculation
-                     */
-                    let (iterator, quad) = self.calculate_mandelbrot_path(origin_re, origin_im);
-                    // result
-                    let state = self.state_from_path_length(iterator, iterator);
-                    self.data_image.set_pixel_mandelbrot(
-                        x,
-                        y,
-                        iterator,
-                        quad,
-                        state,
-                        self.iteration_max,
-                    );
+                    self.calculate_pixel_mandelbrot(x, y, origin_re, origin_im);
                 }
             }
         }
     }
 
-    pub fn calculate_mandelbrot_path(&self, origin_re: f64, origin_im: f64) -> (u64, f64) {
+    /**
+     * Calculation of point re, im, the result is stored to pixel x, y
+     */
+    fn calculate_pixel_mandelbrot(&self, x: usize, y: usize, re: f64, im: f64) {
+        let (iterator, quad, trap) = self.calculate_mandelbrot_path(re, im);
+        // result
+        let state = self.state_from_path_length(iterator, iterator);
+        self.data_image
+            .set_pixel_mandelbrot(x, y, iterator, quad, state, self.iteration_max);
+        if self.orbit_trap.is_trap() {
+            self.data_image.set_pixel_trap(x, y, trap);
+        }
+        if self.coloring_mode.uses_smooth_iteration() && iterator < self.iteration_max {
+            let smooth = smooth_iteration(iterator, quad, self.fractal.degree());
+            self.data_image.set_pixel_smooth(x, y, smooth);
+        }
+    }
+
+    pub fn calculate_mandelbrot_path(&self, origin_re: f64, origin_im: f64) -> (u64, f64, f64) {
         let cb = CALCULATION_BOUNDARY as f64;
 
         let mut m = M::new(origin_re, origin_im);
 
         let mut iterator = 0;
+        let mut trap = NO_TRAP;
         while m.quad() < cb && iterator < self.iteration_max {
             self.fractal.math(&mut m, origin_re, origin_im);
             iterator += 1;
+            if self.orbit_trap.is_trap() {
+                // the closest distance of the calculation path to the trap
+                trap = trap.min(self.orbit_trap.distance(m.re(), m.im()));
+            }
         }
-        (iterator, m.quad())
+        (iterator, m.quad(), trap)
     }
 
     fn translate_all_paths_to_point_grid(&self) {
@@ -718,12 +1173,35 @@ pub fn shuffled_calculation_coordinates() -> Vec<[u64; 2]> {
 
 #[cfg(test)]
 mod tests {
+    use crate::color_space::ColorSpace::Srgb;
+    use crate::euler::EULER_CHANNELS;
+    use crate::files::DensityOutput::Png16;
     use crate::fractal::init_trivial_dynamic_config;
+    use crate::frame_sink::FrameSink::RawRgb;
+    use crate::fractal::FractalCalculationType::StaticImageMandelbrot;
     use crate::machine::init;
+    use crate::palettes::new_palette_by_name_in_space;
+    use crate::palettes::PaletteName::{File, LinearBlue, LinearRed};
+    use crate::resolution_multiplier::ResolutionMultiplier::Square3;
+    use crate::coloring_mode::ColoringMode::Cyclic;
+    use crate::coloring_mode::Cycle;
+    use crate::supersampling::Supersampling::{Adaptive, Grid, Jittered};
+    use crate::supersampling::{ColorSum, Supersampling};
+    use crate::orbit_trap::NO_TRAP;
+    use crate::orbit_trap::OrbitTrap::Point;
     use crate::pixel_states::DomainElementState::{FinishedSuccess, FinishedTooLong};
     use crate::{fractal, machine, pixel_states};
     use pixel_states::DomainElementState::FinishedTooShort;
 
+    #[test]
+    fn test_init_palette_error() {
+        let mut co = fractal::init_trivial_static_config();
+        co.palette = File("missing.gpl".to_string());
+
+        let e = init(&co, fractal::init_trivial_fractal()).err().unwrap();
+        assert!(e.starts_with("missing.gpl: "));
+    }
+
     #[test]
     fn test_state_from_path_length() {
         let machine = machine::init_trivial_static();
@@ -757,7 +1235,7 @@ mod tests {
     fn test_chunk_boundaries() {
         let co = init_trivial_dynamic_config(21);
         let fr = fractal::init_trivial_fractal();
-        let ma = init(&co, fr);
+        let ma = init(&co, fr).unwrap();
 
         let (re_left, re_right, im_top, im_bot) = ma.chunk_boundaries(&[0, 0]);
 
@@ -788,7 +1266,7 @@ mod tests {
     fn test_chunk_calculation_mandelbrot() {
         let co = init_trivial_dynamic_config(21);
         let fr = fractal::init_trivial_fractal();
-        let ma = init(&co, fr);
+        let ma = init(&co, fr).unwrap();
 
         let xy = [0, 0];
         ma.chunk_calculation_mandelbrot(&xy);
@@ -798,6 +1276,224 @@ mod tests {
         assert_eq!(pixel_states::is_finished_any(s), true);
     }
 
+    #[test]
+    fn test_color_cycle() {
+        let mut co = init_trivial_dynamic_config(21);
+        co.fractal_calc_type = StaticImageMandelbrot;
+        co.palette = LinearRed;
+        co.palette_zero = LinearRed;
+        co.color_cycle_frames = 4;
+        co.save_images = false;
+        let fr = fractal::init_trivial_fractal();
+        let ma = init(&co, fr).unwrap();
+
+        for y in 0..21 {
+            for x in 0..21 {
+                ma.data_image.set_pixel_mandelbrot(x, y, 2, 5.0, FinishedSuccess, 3);
+            }
+        }
+        ma.color_mandelbrot_values(&ma.palette, &ma.palette_zero);
+        let first = ma.data_image.color_at(0, 0);
+
+        // the last frame is shifted by 3/4 of spectrum
+        ma.color_cycle();
+        assert_ne!(ma.data_image.color_at(0, 0), first);
+        let last = new_palette_by_name_in_space(&LinearRed, Srgb).rotated(0.75);
+        assert_eq!(ma.data_image.color_at(0, 0), Some(last.spectrum_value(0)));
+    }
+
+    #[test]
+    fn test_color_supersamples() {
+        let mut co = init_trivial_dynamic_config(5);
+        co.fractal_calc_type = StaticImageMandelbrot;
+        co.iteration_max = 20;
+        co.palette = LinearRed;
+        co.palette_zero = LinearBlue;
+        co.coloring_mode = Cyclic(Cycle { period: 0.5, offset: 0.0, mirror: false });
+        co.supersampling = Grid(Square3);
+        let ma = init(&co, fractal::init_trivial_fractal()).unwrap();
+        for y in 0..ma.height_yp {
+            for x in 0..ma.width_xp {
+                let (re, im) = ma.data_image.origin_at(x, y);
+                ma.calculate_pixel_mandelbrot(x, y, re, im);
+            }
+        }
+        let values = ma.data_image.px_values_at(4, 2);
+        let (re, im) = ma.data_image.origin_at(4, 2);
+
+        ma.color_mandelbrot_values(&ma.palette, &ma.palette_zero);
+
+        // the pixel center and its sub-samples colored as pixels of the same image without supersampling
+        co.supersampling = Supersampling::Nothing;
+        let reference = init(&co, fractal::init_trivial_fractal()).unwrap();
+        let plank = ma.area.plank();
+        let mut points = vec![[re, im]];
+        points.extend(ma.data_image.wrap(re, im, Square3, plank));
+        let mut sum = ColorSum::default();
+        let mut sample_colors = Vec::new();
+        for [sample_re, sample_im] in points {
+            for y in 0..reference.height_yp {
+                for x in 0..reference.width_xp {
+                    reference.calculate_pixel_mandelbrot(x, y, sample_re, sample_im);
+                }
+            }
+            reference.color_mandelbrot_values(&reference.palette, &reference.palette_zero);
+            sum.add(reference.data_image.color_at(0, 0).unwrap());
+            sample_colors.push(reference.data_image.color_at(0, 0));
+        }
+        assert_eq!(ma.data_image.color_at(4, 2), sum.average());
+        // the pixel is at an edge, its sub-samples have other colors
+        assert_ne!(ma.data_image.color_at(4, 2), sample_colors[0]);
+        // pixel values are restored
+        assert_eq!(ma.data_image.px_values_at(4, 2), values);
+    }
+
+    #[test]
+    fn test_color_cycle_supersamples() {
+        let mut co = init_trivial_dynamic_config(5);
+        co.fractal_calc_type = StaticImageMandelbrot;
+        co.iteration_max = 20;
+        co.palette = LinearRed;
+        co.palette_zero = LinearBlue;
+        co.supersampling = Jittered(Square3);
+        co.color_cycle_frames = 3;
+        co.save_images = false;
+        let ma = init(&co, fractal::init_trivial_fractal()).unwrap();
+        for y in 0..ma.height_yp {
+            for x in 0..ma.width_xp {
+                let (re, im) = ma.data_image.origin_at(x, y);
+                ma.calculate_pixel_mandelbrot(x, y, re, im);
+            }
+        }
+        let values = ma.data_image.px_values_at(4, 2);
+        ma.color_mandelbrot_values(&ma.palette, &ma.palette_zero);
+        let first = ma.data_image.color_at(4, 2);
+
+        // jittered sub-samples are kept, the same frame has the same colors
+        ma.color_cycle();
+        let last = ma.data_image.color_at(4, 2);
+        ma.color_cycle();
+        assert_eq!(ma.data_image.color_at(4, 2), last);
+        assert_ne!(last, first);
+        ma.recolor_supersamples(&ma.palette, &ma.palette_zero);
+        assert_eq!(ma.data_image.px_values_at(4, 2), values);
+        assert_eq!(ma.supersamples.read().unwrap().layers.len(), 8);
+    }
+
+    #[test]
+    fn test_refined_pixels_adaptive() {
+        let mut co = init_trivial_dynamic_config(5);
+        co.fractal_calc_type = StaticImageMandelbrot;
+        co.supersampling = Adaptive(Square3, 10);
+        let ma = init(&co, fractal::init_trivial_fractal()).unwrap();
+        for y in 0..ma.height_yp {
+            for x in 0..ma.width_xp {
+                ma.data_image.set_pixel_mandelbrot(x, y, 2, 5.0, FinishedSuccess, 3);
+            }
+        }
+        // the last column is inside of the set
+        for y in 0..ma.height_yp {
+            ma.data_image.set_pixel_mandelbrot(ma.width_xp - 1, y, 3, 5.0, FinishedSuccess, 3);
+        }
+
+        let refined = ma.refined_pixels();
+
+        // the last two columns are at the edge
+        assert_eq!(refined.len(), 2 * ma.height_yp);
+        assert!(!refined.contains(&0));
+        assert!(refined.contains(&(ma.width_xp - 1)));
+    }
+
+    #[test]
+    fn test_save_results() {
+        let mut co = init_trivial_dynamic_config(5);
+        co.name = "Machine Results";
+        co.density_output = Png16;
+        co.preview_width = 2;
+        let ma = init(&co, fractal::init_trivial_fractal()).unwrap();
+
+        ma.save_results(7);
+
+        for file_name in [
+            "machine_results_7.jpg",
+            "machine_results_7.json",
+            "machine_results_7_density.png",
+            "machine_results_7_preview.png",
+        ] {
+            assert!(std::fs::metadata(file_name).unwrap().is_file());
+            std::fs::remove_file(file_name).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_save_frame() {
+        let mut co = init_trivial_dynamic_config(5);
+        co.name = "Machine Frame";
+        co.frame_sink = RawRgb("test_machine_frame.rgb");
+        let ma = init(&co, fractal::init_trivial_fractal()).unwrap();
+
+        ma.save_frame(1);
+        co.save_frames = true;
+        let ma_saving = init(&co, fractal::init_trivial_fractal()).unwrap();
+        ma_saving.save_frame(2);
+        drop(ma);
+        drop(ma_saving);
+
+        assert!(!std::path::Path::new("machine_frame_1.jpg").exists());
+        for file_name in ["machine_frame_2.jpg", "machine_frame_2.json", "test_machine_frame.rgb"] {
+            assert!(std::fs::metadata(file_name).unwrap().is_file());
+            std::fs::remove_file(file_name).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_write_frame() {
+        let mut co = init_trivial_dynamic_config(5);
+        co.frame_sink = RawRgb("test_machine_frames.rgb");
+        let ma = init(&co, fractal::init_trivial_fractal()).unwrap();
+
+        ma.write_frame(1);
+        ma.write_frame(2);
+        drop(ma);
+
+        let bytes = std::fs::read("test_machine_frames.rgb").unwrap();
+        std::fs::remove_file("test_machine_frames.rgb").unwrap();
+        assert_eq!(bytes.len(), 2 * 4 * 4 * 3);
+    }
+
+    #[test]
+    fn test_metadata() {
+        let mut co = init_trivial_dynamic_config(5);
+        co.palette = LinearRed;
+        let ma = init(&co, fractal::init_trivial_fractal()).unwrap();
+        ma.zoom_in_by(0.5);
+
+        let m = ma.metadata(3);
+
+        assert_eq!(m.width_x, 4);
+        assert_eq!(m.width_re, co.width_re * 0.5);
+        assert_eq!(m.frame, 3);
+        assert!(m.entries().contains(&("palette", "LinearRed".to_string())));
+        assert_eq!(m.spectral_channels, EULER_CHANNELS);
+        assert_eq!(m.fractal_definition, None);
+    }
+
+    #[test]
+    fn test_save_results_min_save_width() {
+        let mut co = init_trivial_dynamic_config(5);
+        co.name = "Machine Narrow";
+        co.min_save_width = 600;
+        co.preview_width = 2;
+        let ma = init(&co, fractal::init_trivial_fractal()).unwrap();
+
+        ma.save_results(0);
+
+        // only preview is saved
+        assert!(std::fs::metadata("machine_narrow_0.jpg").is_err());
+        assert!(std::fs::metadata("machine_narrow_0_preview.png").unwrap().is_file());
+        std::fs::remove_file("machine_narrow_0_preview.png").unwrap();
+    }
+
     #[test]
     fn test_calculate_path() {
         // prepare test data
@@ -810,11 +1506,25 @@ mod tests {
         assert_eq!(length, 0);
     }
 
+    #[test]
+    fn test_calculate_mandelbrot_path_orbit_trap() {
+        let mut machine = machine::init_trivial_static();
+
+        let (_, _, trap) = machine.calculate_mandelbrot_path(0.5, 0.0);
+        assert_eq!(trap, NO_TRAP);
+
+        // path 0.5, 0.75, 1.0625
+        machine.orbit_trap = Point(1.0, 0.0);
+        let (iterator, _, trap) = machine.calculate_mandelbrot_path(0.5, 0.0);
+        assert_eq!(iterator, 3);
+        assert_eq!(trap, 0.0625);
+    }
+
     #[test]
     fn test_recalculate_pixels_positions_for_next_calculation() {
         let co = init_trivial_dynamic_config(7);
         let fr = fractal::init_trivial_fractal();
-        let ma = init(&co, fr);
+        let ma = init(&co, fr).unwrap();
 
         let di = &ma.data_image;
 
@@ -0,0 +1,173 @@
+// The method used for mapped coloring is
+// - Find the highest value of all screen pixels
+// - Map each value by a function to <0, 1>, relative to the highest value
+// - Color by interpolated spectrum color at that position
+// Unlike perfect coloring, relative brightness of pixels stays meaningful
+
+use crate::coloring_mode::ColoringMode;
+use crate::constants::COLORING_THRESHOLD;
+use crate::data_image::DataImage;
+use crate::palette::Palette;
+
+/**
+ * Value mapped to <0, 1>
+ * - max: the highest value
+ * - scale: softening of asinh, values much smaller than scale are linear, much higher logarithmic
+ */
+pub fn map_value(mode: ColoringMode, v: f64, max: f64, scale: f64) -> f64 {
+    if max <= 0.0 {
+        return 0.0;
+    }
+    let t = match mode {
+        ColoringMode::Log => v.ln_1p() / max.ln_1p(),
+        ColoringMode::Power(gamma) => (v / max).powf(gamma),
+        ColoringMode::Asinh => (v / scale).asinh() / (max / scale).asinh(),
+        _ => v / max,
+    };
+    t.clamp(0.0, 1.0)
+}
+
+pub fn color_nebula_values_mapped(data: &DataImage, palette: &Palette, mode: ColoringMode) {
+    println!("color_nebula_values_mapped() {:?}", mode);
+
+    let mut values: Vec<f64> = Vec::new();
+    for y in 0..data.height_yp {
+        for x in 0..data.width_xp {
+            let v = data.value_at(x, y);
+            if v > COLORING_THRESHOLD {
+                values.push(v as f64);
+            }
+        }
+    }
+    let (max, scale) = max_and_median(values);
+
+    for y in 0..data.height_yp {
+        for x in 0..data.width_xp {
+            let v = data.value_at(x, y);
+            let t = if v <= COLORING_THRESHOLD {
+                // color zero-value elements and low-value-noise with the darkest color
+                0.0
+            } else {
+                map_value(mode, v as f64, max, scale)
+            };
+            data.color(x, y, palette.spectrum_value_at_fraction(t));
+        }
+    }
+}
+
+pub fn color_mandelbrot_values_mapped(
+    data: &DataImage,
+    palette: &Palette,
+    palette_zero: &Palette,
+    mode: ColoringMode,
+) {
+    println!("color_mandelbrot_values_mapped() {:?}", mode);
+
+    let mut values: Vec<f64> = Vec::new();
+    let mut quads: Vec<f64> = Vec::new();
+    for y in 0..data.height_yp {
+        for x in 0..data.width_xp {
+            let (value, _, quad, _) = data.values_state_quad_color_at(x, y);
+            if value == 0 {
+                quads.push(quad);
+            } else {
+                values.push(value as f64);
+            }
+        }
+    }
+    let (max, scale) = max_and_median(values);
+    let (max_zero, scale_zero) = max_and_median(quads);
+
+    for y in 0..data.height_yp {
+        for x in 0..data.width_xp {
+            let (value, _, quad, _) = data.values_state_quad_color_at(x, y);
+            if value == 0 {
+                // Paint insides of Mandelbrot set by quad
+                if !palette_zero.spectrum.is_empty() {
+                    let t = map_value(mode, quad, max_zero, scale_zero);
+                    data.color(x, y, palette_zero.spectrum_value_at_fraction(t));
+                }
+            } else {
+                let t = map_value(mode, value as f64, max, scale);
+                data.color(x, y, palette.spectrum_value_at_fraction(t));
+            }
+        }
+    }
+}
+
+pub(crate) fn max_and_median(mut values: Vec<f64>) -> (f64, f64) {
+    if values.is_empty() {
+        return (0.0, 1.0);
+    }
+    values.sort_by(|first, second| first.total_cmp(second));
+    let median = values[values.len() / 2];
+    (values[values.len() - 1], if median > 0.0 { median } else { 1.0 })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::area;
+    use crate::coloring_mode::ColoringMode::{Asinh, Linear, Log, Power};
+    use crate::data_image::init;
+    use crate::fractal::init_trivial_dynamic_config;
+    use crate::mapped_color_distribution::{
+        color_mandelbrot_values_mapped, color_nebula_values_mapped, map_value, max_and_median,
+    };
+    use crate::palette::init_trivial;
+    use crate::pixel_states::DomainElementState::FinishedSuccess;
+    use image::Rgb;
+
+    #[test]
+    fn test_map_value() {
+        assert_eq!(map_value(Linear, 25.0, 100.0, 1.0), 0.25);
+        assert_eq!(map_value(Power(0.5), 25.0, 100.0, 1.0), 0.5);
+        assert_eq!(map_value(Log, 9.0, 99.0, 1.0), 0.5);
+        assert_eq!(map_value(Asinh, 100.0, 100.0, 3.0), 1.0);
+        assert!(map_value(Asinh, 1.0, 100.0, 3.0) > map_value(Linear, 1.0, 100.0, 3.0));
+        assert_eq!(map_value(Linear, 5.0, 0.0, 1.0), 0.0);
+    }
+
+    #[test]
+    fn test_max_and_median() {
+        assert_eq!(max_and_median(vec![3.0, 1.0, 2.0]), (3.0, 2.0));
+        assert_eq!(max_and_median(Vec::new()), (0.0, 1.0));
+    }
+
+    #[test]
+    fn test_color_nebula_values_mapped() {
+        let c = init_trivial_dynamic_config(3);
+        let a = area::init(&c);
+        let di = init(&c, &a);
+        let p = init_trivial();
+
+        di.set_pixel_mandelbrot(0, 0, 10, 0.0, FinishedSuccess, 100);
+        di.set_pixel_mandelbrot(1, 0, 20, 0.0, FinishedSuccess, 100);
+        color_nebula_values_mapped(&di, &p, Linear);
+
+        assert_eq!(di.color_at(0, 0), Some(Rgb([0, 255, 0])));
+        assert_eq!(di.color_at(1, 0), Some(Rgb([0, 0, 255])));
+        // zero value
+        assert_eq!(di.color_at(2, 2), Some(Rgb([255, 0, 0])));
+    }
+
+    #[test]
+    fn test_color_mandelbrot_values_mapped() {
+        let c = init_trivial_dynamic_config(3);
+        let a = area::init(&c);
+        let di = init(&c, &a);
+        let p = init_trivial();
+
+        for y in 0..3 {
+            for x in 0..3 {
+                di.set_pixel_mandelbrot(x, y, 5, 4.0, FinishedSuccess, 10);
+            }
+        }
+        di.set_pixel_mandelbrot(0, 0, 10, 2.0, FinishedSuccess, 10);
+        di.set_pixel_mandelbrot(1, 0, 10, 1.0, FinishedSuccess, 10);
+        color_mandelbrot_values_mapped(&di, &p, &p, Power(1.0));
+
+        assert_eq!(di.color_at(2, 2), Some(Rgb([0, 0, 255])));
+        assert_eq!(di.color_at(0, 0), Some(Rgb([0, 0, 255])));
+        assert_eq!(di.color_at(1, 0), Some(Rgb([0, 255, 0])));
+    }
+}
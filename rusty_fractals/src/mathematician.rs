@@ -15,6 +15,49 @@ struct Mathematician {
     happy: RwLock<HashSet<u64>>,
 }
 
+/**
+ * Integer sequences, for coloring and formulas
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Sequence {
+    Prime,
+    Fibonacci,
+    Perfect,
+    Square,
+    Triangular,
+    Lucas,
+    Lazy,
+    Happy,
+}
+
+impl Sequence {
+    pub fn init(&self, max: u64) {
+        match self {
+            Sequence::Prime => init_primes(max),
+            Sequence::Fibonacci => init_fibonacci(max),
+            Sequence::Perfect => init_perfect(max),
+            Sequence::Square => init_squares(max),
+            Sequence::Triangular => init_triangular(max),
+            Sequence::Lucas => init_lucas(max),
+            Sequence::Lazy => init_lazy(max),
+            Sequence::Happy => init_happy(max),
+        }
+    }
+
+    pub fn contains(&self, n: u64) -> bool {
+        match self {
+            Sequence::Prime => is_prime(n),
+            Sequence::Fibonacci => is_fibonacci(n),
+            Sequence::Perfect => is_perfect(n),
+            Sequence::Square => is_square(n),
+            Sequence::Triangular => is_triangular(n),
+            Sequence::Lucas => is_lucas(n),
+            Sequence::Lazy => is_lazy(n),
+            Sequence::Happy => is_happy(n),
+        }
+    }
+}
+
 static MATHEMATICIAN: Lazy<Mathematician> = Lazy::new(|| Mathematician::new());
 
 impl Mathematician {
@@ -306,9 +349,19 @@ mod tests {
     use crate::mathematician::{
         init_fibonacci, init_happy, init_lazy, init_lucas, init_perfect, init_primes, init_squares,
         init_triangular, is_fibonacci, is_happy, is_lazy, is_lucas, is_outside_cardioid,
-        is_outside_circle, is_perfect, is_prime, is_square, is_triangular,
+        is_outside_circle, is_perfect, is_prime, is_square, is_triangular, Sequence,
     };
 
+    #[test]
+    fn test_sequence() {
+        Sequence::Square.init(100);
+        assert!(Sequence::Square.contains(49));
+        assert!(!Sequence::Square.contains(50));
+
+        Sequence::Lucas.init(100);
+        assert!(Sequence::Lucas.contains(47));
+    }
+
     #[test]
     fn test_is_outside_cardioid() {
         assert_eq!(is_outside_cardioid(0.0, 0.0), false);
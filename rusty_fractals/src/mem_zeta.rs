@@ -0,0 +1,242 @@
+use crate::fractal::MemType;
+use crate::mem::Mem;
+use std::f64::consts::PI;
+
+// Borwein series length, error is about 3 / (3 + sqrt(8))^n
+const BORWEIN_N: usize = 50;
+
+// Lanczos approximation of Gamma function, g = 7
+const LANCZOS_G: f64 = 7.0;
+const LANCZOS_P: [f64; 9] = [
+    0.999_999_999_999_809_9,
+    676.520_368_121_885_1,
+    -1_259.139_216_722_402_8,
+    771.323_428_777_653_1,
+    -176.615_029_162_140_6,
+    12.507_343_278_686_905,
+    -0.138_571_095_265_720_12,
+    9.984_369_578_019_572e-6,
+    1.505_632_735_149_311_6e-7,
+];
+
+/**
+ * Memory object for Riemann Zeta fractal
+ */
+pub struct MemZeta {
+    pub m: Mem,
+}
+
+impl MemZeta {
+    pub fn plus(&mut self, r: f64, i: f64) {
+        self.m.plus(r, i);
+    }
+
+    pub fn square(&mut self) {
+        self.m.square();
+    }
+
+    /** z = ζ(z) */
+    pub fn zeta(&mut self) {
+        let (re, im) = zeta(self.m.re, self.m.im);
+        self.m.re = re;
+        self.m.im = im;
+    }
+}
+
+impl MemType<MemZeta> for MemZeta {
+    fn new(re: f64, im: f64) -> MemZeta {
+        MemZeta {
+            m: Mem { re, im, it: 0 },
+        }
+    }
+
+    fn quad(&self) -> f64 {
+        self.m.quad()
+    }
+
+    fn re(&self) -> f64 {
+        self.m.re
+    }
+
+    fn im(&self) -> f64 {
+        self.m.im
+    }
+}
+
+/**
+ * Riemann Zeta function ζ(s) for complex s = re + i im
+ * - Borwein's algorithm for Re(s) >= 0
+ * - functional equation for Re(s) < 0
+ *
+ * ζ(s) = 2^s π^(s-1) sin(πs/2) Γ(1-s) ζ(1-s)
+ */
+pub fn zeta(re: f64, im: f64) -> (f64, f64) {
+    if re >= 0.0 {
+        return zeta_borwein(re, im);
+    }
+    let s = (re, im);
+    let one_minus_s = (1.0 - re, -im);
+
+    let two_s = real_pow(2.0, s);
+    let pi_s = real_pow(PI, (re - 1.0, im));
+    let sin = sin((PI * re / 2.0, PI * im / 2.0));
+    let gamma = gamma(one_minus_s);
+    let zeta = zeta_borwein(one_minus_s.0, one_minus_s.1);
+
+    mul(mul(mul(two_s, pi_s), mul(sin, gamma)), zeta)
+}
+
+/**
+ * ζ(s) = η(s) / (1 - 2^(1-s))
+ * η(s) = -1/d_n Σ (-1)^k (d_k - d_n) / (k+1)^s
+ */
+fn zeta_borwein(re: f64, im: f64) -> (f64, f64) {
+    let n = BORWEIN_N;
+    let nf = n as f64;
+
+    // d_k = n Σ (n+i-1)! 4^i / ((n-i)! (2i)!)
+    let mut d = Vec::with_capacity(n + 1);
+    let mut term = 1.0;
+    let mut sum = term;
+    d.push(sum);
+    for i in 0..n {
+        let i = i as f64;
+        term *= 4.0 * (nf + i) * (nf - i) / ((2.0 * i + 1.0) * (2.0 * i + 2.0));
+        sum += term;
+        d.push(sum);
+    }
+    let d_n = d[n];
+
+    let mut eta = (0.0, 0.0);
+    let mut sign = 1.0;
+    for (k, d_k) in d.iter().take(n).enumerate() {
+        let c = sign * (d_k - d_n);
+        sign = -sign;
+        // (k+1)^-s
+        let p = real_pow((k + 1) as f64, (-re, -im));
+        eta.0 += c * p.0;
+        eta.1 += c * p.1;
+    }
+    eta.0 /= -d_n;
+    eta.1 /= -d_n;
+
+    let two = real_pow(2.0, (1.0 - re, -im));
+    div(eta, (1.0 - two.0, -two.1))
+}
+
+/**
+ * Lanczos approximation, for Re(z) >= 0.5
+ */
+fn gamma(z: (f64, f64)) -> (f64, f64) {
+    let z = (z.0 - 1.0, z.1);
+    let mut x = (LANCZOS_P[0], 0.0);
+    for (i, p) in LANCZOS_P.iter().enumerate().skip(1) {
+        let t = div((*p, 0.0), (z.0 + i as f64, z.1));
+        x.0 += t.0;
+        x.1 += t.1;
+    }
+    let t = (z.0 + LANCZOS_G + 0.5, z.1);
+    // t^(z+0.5) e^-t
+    let tp = exp(mul((z.0 + 0.5, z.1), ln(t)));
+    let et = exp((-t.0, -t.1));
+    let r = mul(mul(tp, et), x);
+    let sq = (2.0 * PI).sqrt();
+    (sq * r.0, sq * r.1)
+}
+
+fn mul(a: (f64, f64), b: (f64, f64)) -> (f64, f64) {
+    (a.0 * b.0 - a.1 * b.1, a.0 * b.1 + a.1 * b.0)
+}
+
+fn div(a: (f64, f64), b: (f64, f64)) -> (f64, f64) {
+    let q = b.0 * b.0 + b.1 * b.1;
+    ((a.0 * b.0 + a.1 * b.1) / q, (a.1 * b.0 - a.0 * b.1) / q)
+}
+
+fn exp(a: (f64, f64)) -> (f64, f64) {
+    let e = a.0.exp();
+    (e * a.1.cos(), e * a.1.sin())
+}
+
+fn ln(a: (f64, f64)) -> (f64, f64) {
+    ((a.0 * a.0 + a.1 * a.1).sqrt().ln(), a.1.atan2(a.0))
+}
+
+fn sin(a: (f64, f64)) -> (f64, f64) {
+    (a.0.sin() * a.1.cosh(), a.0.cos() * a.1.sinh())
+}
+
+/**
+ * base^s for real positive base
+ */
+fn real_pow(base: f64, s: (f64, f64)) -> (f64, f64) {
+    let l = base.ln();
+    exp((s.0 * l, s.1 * l))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fractal::MemType;
+    use crate::mem_zeta::{gamma, zeta, MemZeta};
+    use std::f64::consts::PI;
+
+    const TOLERANCE: f64 = 1e-10;
+
+    fn assert_close(result: (f64, f64), expected: (f64, f64)) {
+        assert!(
+            (result.0 - expected.0).abs() < TOLERANCE && (result.1 - expected.1).abs() < TOLERANCE,
+            "{:?} != {:?}",
+            result,
+            expected
+        );
+    }
+
+    #[test]
+    fn test_zeta_positive() {
+        assert_close(zeta(2.0, 0.0), (PI * PI / 6.0, 0.0));
+        assert_close(zeta(3.0, 0.0), (1.202_056_903_159_594_2, 0.0));
+        assert_close(zeta(4.0, 0.0), (PI.powi(4) / 90.0, 0.0));
+    }
+
+    #[test]
+    fn test_zeta_zero() {
+        assert_close(zeta(0.0, 0.0), (-0.5, 0.0));
+        assert_close(zeta(0.5, 0.0), (-1.460_354_508_809_586_8, 0.0));
+    }
+
+    #[test]
+    fn test_zeta_negative() {
+        assert_close(zeta(-1.0, 0.0), (-1.0 / 12.0, 0.0));
+        assert_close(zeta(-2.0, 0.0), (0.0, 0.0));
+        assert_close(zeta(-3.0, 0.0), (1.0 / 120.0, 0.0));
+    }
+
+    #[test]
+    fn test_zeta_first_non_trivial_zero() {
+        assert_close(zeta(0.5, 14.134_725_141_734_693), (0.0, 0.0));
+    }
+
+    #[test]
+    fn test_zeta_complex() {
+        // ζ(2 + i)
+        assert_close(
+            zeta(2.0, 1.0),
+            (1.150_355_703_254_9, -0.437_530_865_919_607_6),
+        );
+    }
+
+    #[test]
+    fn test_gamma() {
+        assert_close(gamma((5.0, 0.0)), (24.0, 0.0));
+        assert_close(gamma((0.5, 0.0)), (PI.sqrt(), 0.0));
+    }
+
+    #[test]
+    fn test_zeta_mem() {
+        let mut mz = MemZeta::new(2.0, 0.0);
+        mz.zeta();
+        mz.plus(-1.0, 0.0);
+        assert!((mz.re() - 0.644_934_066_848_226_4).abs() < TOLERANCE);
+        assert_eq!(mz.im(), 0.0);
+    }
+}
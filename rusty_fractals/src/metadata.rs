@@ -0,0 +1,641 @@
+use crate::color_space::{ColorSpace, HueDirection};
+use crate::coloring_mode::{ColoringMode, Cycle};
+use crate::euler::{Channel, Classifier, Subject, EULER_CHANNELS};
+use crate::fractal::FractalCalculationType::{
+    DynamicSequenceNebula, StaticImageMandelbrot, StaticImageNebula, StaticSequenceMandelbrot,
+    StaticSpectralImageEuler,
+};
+use crate::fractal::OrbitType::{Finite, Ignore, Infinite};
+use crate::fractal::{FractalCalculationType, OrbitType};
+use crate::mathematician::Sequence;
+use crate::orbit_trap::{OrbitTrap, OrbitTrapColoring};
+use crate::palettes::PaletteName;
+use crate::resolution_multiplier::ResolutionMultiplier;
+use crate::resolution_multiplier::ResolutionMultiplier::{
+    Single, Square101, Square11, Square2, Square3, Square5, Square51, Square9,
+};
+use crate::supersampling::Supersampling;
+
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/**
+ * Everything needed to render the saved image again
+ * - embedded into PNG as tEXt chunks
+ * - saved as sidecar JSON next to JPG and WebP
+ */
+#[derive(Clone, Debug)]
+pub struct Metadata {
+    pub name: String,
+    pub fractal_calc_type: FractalCalculationType,
+    pub width_x: usize,
+    pub height_y: usize,
+    pub center_re: f64,
+    pub center_im: f64,
+    pub width_re: f64,
+    pub iteration_min: u64,
+    pub iteration_max: u64,
+    pub orbits: OrbitType,
+    pub resolution_multiplier: ResolutionMultiplier,
+    pub palette: PaletteName,
+    pub palette_zero: PaletteName,
+    pub palette_space: ColorSpace,
+    // fraction of palette length, palettes rotated by color cycling
+    pub palette_shift: f64,
+    pub coloring_mode: ColoringMode,
+    pub orbit_trap: OrbitTrap,
+    pub orbit_trap_coloring: OrbitTrapColoring,
+    pub supersampling: Supersampling,
+    // Euler specific
+    pub spectral_channels: Vec<Channel>,
+    // formula or polynomial text of fractals defined at runtime
+    pub fractal_definition: Option<String>,
+    pub frame: u64,
+    // local time when the image was saved
+    pub render_time: String,
+    pub version: String,
+}
+
+impl Metadata {
+    /**
+     * Metadata as key and text value pairs, f64 values are printed exactly
+     */
+    pub fn entries(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("name", self.name.clone()),
+            ("fractal_calc_type", format!("{:?}", self.fractal_calc_type)),
+            ("width_x", self.width_x.to_string()),
+            ("height_y", self.height_y.to_string()),
+            ("center_re", self.center_re.to_string()),
+            ("center_im", self.center_im.to_string()),
+            ("width_re", self.width_re.to_string()),
+            ("iteration_min", self.iteration_min.to_string()),
+            ("iteration_max", self.iteration_max.to_string()),
+            ("orbits", format!("{:?}", self.orbits)),
+            ("resolution_multiplier", format!("{:?}", self.resolution_multiplier)),
+            ("palette", format!("{:?}", self.palette)),
+            ("palette_zero", format!("{:?}", self.palette_zero)),
+            ("palette_space", format!("{:?}", self.palette_space)),
+            ("palette_shift", self.palette_shift.to_string()),
+            ("coloring_mode", coloring_mode_text(self.coloring_mode)),
+            ("orbit_trap", format!("{:?}", self.orbit_trap)),
+            ("orbit_trap_coloring", format!("{:?}", self.orbit_trap_coloring)),
+            ("supersampling", format!("{:?}", self.supersampling)),
+            ("spectral_channels", channels_text(&self.spectral_channels)),
+            ("fractal_definition", self.fractal_definition.clone().unwrap_or_default()),
+            ("frame", self.frame.to_string()),
+            ("render_time", self.render_time.clone()),
+            ("version", self.version.clone()),
+        ]
+    }
+
+    /**
+     * Flat JSON object, all values are strings
+     */
+    pub fn to_json(&self) -> String {
+        let lines: Vec<String> = self
+            .entries()
+            .iter()
+            .map(|(key, value)| format!("  \"{}\": \"{}\"", key, escape_json(value)))
+            .collect();
+        format!("{{\n{}\n}}\n", lines.join(",\n"))
+    }
+
+    /**
+     * Inverse of entries(), unknown keys are ignored
+     * coloring keys missing in metadata of older versions keep the default values
+     */
+    pub fn from_entries(entries: &[(String, String)]) -> Result<Metadata, String> {
+        let get = |key: &str| {
+            entries
+                .iter()
+                .find(|(k, _)| k == key)
+                .map(|(_, v)| v.as_str())
+                .ok_or(format!("metadata '{}' missing", key))
+        };
+        let get_or = |key: &str, default: &'static str| get(key).unwrap_or(default);
+        let m = Metadata {
+            name: get("name")?.to_string(),
+            fractal_calc_type: parse_calc_type(get("fractal_calc_type")?)?,
+            width_x: parse_number(get("width_x")?)?,
+            height_y: parse_number(get("height_y")?)?,
+            center_re: parse_number(get("center_re")?)?,
+            center_im: parse_number(get("center_im")?)?,
+            width_re: parse_number(get("width_re")?)?,
+            iteration_min: parse_number(get("iteration_min")?)?,
+            iteration_max: parse_number(get("iteration_max")?)?,
+            orbits: parse_orbits(get("orbits")?)?,
+            resolution_multiplier: parse_resolution_multiplier(get("resolution_multiplier")?)?,
+            palette: parse_palette(get("palette")?)?,
+            palette_zero: parse_palette(get("palette_zero")?)?,
+            palette_space: parse_color_space(get_or("palette_space", "Srgb"))?,
+            palette_shift: parse_number(get_or("palette_shift", "0"))?,
+            coloring_mode: parse_coloring_mode(get_or("coloring_mode", "Perfect"))?,
+            orbit_trap: parse_orbit_trap(get_or("orbit_trap", "Nothing"))?,
+            orbit_trap_coloring: parse_orbit_trap_coloring(get_or("orbit_trap_coloring", "Instead"))?,
+            supersampling: parse_supersampling(get_or("supersampling", "Nothing"))?,
+            spectral_channels: match get("spectral_channels") {
+                Ok(text) => parse_channels(text)?,
+                Err(_) => EULER_CHANNELS.to_vec(),
+            },
+            fractal_definition: get("fractal_definition")
+                .ok()
+                .filter(|d| !d.is_empty())
+                .map(|d| d.to_string()),
+            frame: parse_number(get("frame")?)?,
+            render_time: get("render_time")?.to_string(),
+            version: get("version")?.to_string(),
+        };
+        m.validate()?;
+        Ok(m)
+    }
+
+    /**
+     * Values which can't render any image
+     */
+    fn validate(&self) -> Result<(), String> {
+        if self.width_x == 0 || self.height_y == 0 {
+            return Err(format!("invalid image size {}x{}", self.width_x, self.height_y));
+        }
+        if !(self.width_re.is_finite() && self.width_re > 0.0) {
+            return Err(format!("invalid width_re {}", self.width_re));
+        }
+        if !(self.center_re.is_finite() && self.center_im.is_finite()) {
+            return Err(format!("invalid center {}, {}", self.center_re, self.center_im));
+        }
+        if self.iteration_max == 0 || self.iteration_min > self.iteration_max {
+            return Err(format!("invalid iterations {} to {}", self.iteration_min, self.iteration_max));
+        }
+        self.coloring_mode.validate()
+    }
+
+    /**
+     * Inverse of to_json()
+     */
+    pub fn from_json(json: &str) -> Result<Metadata, String> {
+        Metadata::from_entries(&parse_json(json)?)
+    }
+}
+
+fn parse_number<T: std::str::FromStr>(text: &str) -> Result<T, String> {
+    text.parse().map_err(|_| format!("invalid number '{}'", text))
+}
+
+fn parse_calc_type(text: &str) -> Result<FractalCalculationType, String> {
+    match text {
+        "StaticImageNebula" => Ok(StaticImageNebula),
+        "DynamicSequenceNebula" => Ok(DynamicSequenceNebula),
+        "StaticImageMandelbrot" => Ok(StaticImageMandelbrot),
+        "StaticSequenceMandelbrot" => Ok(StaticSequenceMandelbrot),
+        "StaticSpectralImageEuler" => Ok(StaticSpectralImageEuler),
+        _ => Err(format!("unknown calculation type '{}'", text)),
+    }
+}
+
+fn parse_orbits(text: &str) -> Result<OrbitType, String> {
+    match text {
+        "Ignore" => Ok(Ignore),
+        "Finite" => Ok(Finite),
+        "Infinite" => Ok(Infinite),
+        _ => Err(format!("unknown orbit type '{}'", text)),
+    }
+}
+
+pub(crate) fn parse_resolution_multiplier(text: &str) -> Result<ResolutionMultiplier, String> {
+    match text {
+        "Single" => Ok(Single),
+        "Square2" => Ok(Square2),
+        "Square3" => Ok(Square3),
+        "Square5" => Ok(Square5),
+        "Square9" => Ok(Square9),
+        "Square11" => Ok(Square11),
+        "Square51" => Ok(Square51),
+        "Square101" => Ok(Square101),
+        _ => Err(format!("unknown resolution multiplier '{}'", text)),
+    }
+}
+
+/**
+ * Variant name and its arguments, "Point(0.5, -1)" is ("Point", ["0.5", "-1"])
+ */
+fn parse_variant(text: &str) -> (&str, Vec<&str>) {
+    match text.split_once('(') {
+        Some((name, arguments)) => {
+            let arguments = arguments.strip_suffix(')').unwrap_or(arguments);
+            (name, arguments.split(", ").collect())
+        }
+        None => (text, Vec::new()),
+    }
+}
+
+fn parse_color_space(text: &str) -> Result<ColorSpace, String> {
+    let (name, args) = parse_variant(text);
+    match (name, args.as_slice()) {
+        ("Srgb", []) => Ok(ColorSpace::Srgb),
+        ("LinearRgb", []) => Ok(ColorSpace::LinearRgb),
+        ("OkLab", []) => Ok(ColorSpace::OkLab),
+        ("OkLch", ["Shorter"]) => Ok(ColorSpace::OkLch(HueDirection::Shorter)),
+        ("OkLch", ["Longer"]) => Ok(ColorSpace::OkLch(HueDirection::Longer)),
+        ("OkLch", ["Increasing"]) => Ok(ColorSpace::OkLch(HueDirection::Increasing)),
+        ("OkLch", ["Decreasing"]) => Ok(ColorSpace::OkLch(HueDirection::Decreasing)),
+        _ => Err(format!("unknown color space '{}'", text)),
+    }
+}
+
+/**
+ * Cycle is written by its values only, as Cyclic(period, offset, mirror)
+ */
+fn coloring_mode_text(mode: ColoringMode) -> String {
+    match mode {
+        ColoringMode::Cyclic(c) => format!("Cyclic({:?}, {:?}, {})", c.period, c.offset, c.mirror),
+        mode => format!("{:?}", mode),
+    }
+}
+
+fn parse_coloring_mode(text: &str) -> Result<ColoringMode, String> {
+    let (name, args) = parse_variant(text);
+    match (name, args.as_slice()) {
+        ("Perfect", []) => Ok(ColoringMode::Perfect),
+        ("Smooth", []) => Ok(ColoringMode::Smooth),
+        ("Cyclic", [period, offset, mirror]) => Ok(ColoringMode::Cyclic(Cycle {
+            period: parse_number(period)?,
+            offset: parse_number(offset)?,
+            mirror: parse_number(mirror)?,
+        })),
+        ("Linear", []) => Ok(ColoringMode::Linear),
+        ("Log", []) => Ok(ColoringMode::Log),
+        ("Power", [gamma]) => Ok(ColoringMode::Power(parse_number(gamma)?)),
+        ("Asinh", []) => Ok(ColoringMode::Asinh),
+        _ => Err(format!("unknown coloring mode '{}'", text)),
+    }
+}
+
+fn parse_orbit_trap(text: &str) -> Result<OrbitTrap, String> {
+    let (name, args) = parse_variant(text);
+    let values: Vec<f64> = args.iter().map(|a| parse_number(a)).collect::<Result<_, _>>()?;
+    match (name, values.as_slice()) {
+        ("Nothing", []) => Ok(OrbitTrap::Nothing),
+        ("Point", [re, im]) => Ok(OrbitTrap::Point(*re, *im)),
+        ("Line", [re, im, angle]) => Ok(OrbitTrap::Line(*re, *im, *angle)),
+        ("Circle", [re, im, radius]) => Ok(OrbitTrap::Circle(*re, *im, *radius)),
+        ("Cross", [re, im]) => Ok(OrbitTrap::Cross(*re, *im)),
+        _ => Err(format!("unknown orbit trap '{}'", text)),
+    }
+}
+
+fn parse_orbit_trap_coloring(text: &str) -> Result<OrbitTrapColoring, String> {
+    match text {
+        "Instead" => Ok(OrbitTrapColoring::Instead),
+        "Alongside" => Ok(OrbitTrapColoring::Alongside),
+        _ => Err(format!("unknown orbit trap coloring '{}'", text)),
+    }
+}
+
+fn parse_supersampling(text: &str) -> Result<Supersampling, String> {
+    let (name, args) = parse_variant(text);
+    match (name, args.as_slice()) {
+        ("Nothing", []) => Ok(Supersampling::Nothing),
+        ("Grid", [rm]) => Ok(Supersampling::Grid(parse_resolution_multiplier(rm)?)),
+        ("Jittered", [rm]) => Ok(Supersampling::Jittered(parse_resolution_multiplier(rm)?)),
+        ("Adaptive", [rm, difference]) => Ok(Supersampling::Adaptive(
+            parse_resolution_multiplier(rm)?,
+            parse_number(difference)?,
+        )),
+        _ => Err(format!("unknown supersampling '{}'", text)),
+    }
+}
+
+/**
+ * Channels separated by ';', classifiers and the color row of each as
+ * Prime(PathLength) Fibonacci(ElementIndex) -> 0.0, 1.0, 0.0
+ */
+fn channels_text(channels: &[Channel]) -> String {
+    let channels: Vec<String> = channels
+        .iter()
+        .map(|channel| {
+            let classifiers: Vec<String> = channel
+                .classifiers
+                .iter()
+                .map(|c| format!("{:?}({:?})", c.sequence, c.subject))
+                .collect();
+            let [r, g, b] = channel.color;
+            format!("{} -> {:?}, {:?}, {:?}", classifiers.join(" "), r, g, b)
+        })
+        .collect();
+    channels.join("; ")
+}
+
+fn parse_channels(text: &str) -> Result<Vec<Channel>, String> {
+    text.split(';')
+        .map(|channel| {
+            let (classifiers, color) = channel
+                .split_once("->")
+                .ok_or(format!("invalid spectral channel '{}'", channel))?;
+            let classifiers = classifiers
+                .split_whitespace()
+                .map(parse_classifier)
+                .collect::<Result<Vec<Classifier>, String>>()?;
+            let color = color
+                .split(',')
+                .map(|c| parse_number(c.trim()))
+                .collect::<Result<Vec<f64>, String>>()?;
+            let color = color
+                .try_into()
+                .map_err(|_| format!("invalid spectral channel color '{}'", channel))?;
+            Ok(Channel { classifiers: classifiers.into(), color })
+        })
+        .collect()
+}
+
+fn parse_classifier(text: &str) -> Result<Classifier, String> {
+    let (name, args) = parse_variant(text);
+    let sequence = match name {
+        "Prime" => Sequence::Prime,
+        "Fibonacci" => Sequence::Fibonacci,
+        "Perfect" => Sequence::Perfect,
+        "Square" => Sequence::Square,
+        "Triangular" => Sequence::Triangular,
+        "Lucas" => Sequence::Lucas,
+        "Lazy" => Sequence::Lazy,
+        "Happy" => Sequence::Happy,
+        _ => return Err(format!("unknown classifier '{}'", text)),
+    };
+    let subject = match args.as_slice() {
+        ["ElementIndex"] => Subject::ElementIndex,
+        ["PathLength"] => Subject::PathLength,
+        ["Iteration"] => Subject::Iteration,
+        _ => return Err(format!("unknown classifier '{}'", text)),
+    };
+    Ok(Classifier { sequence, subject })
+}
+
+pub(crate) fn parse_palette(text: &str) -> Result<PaletteName, String> {
+    if let Some(path) = text.strip_prefix("File(\"").and_then(|t| t.strip_suffix("\")")) {
+        return Ok(PaletteName::File(path.replace("\\\"", "\"").replace("\\\\", "\\")));
+    }
+    match text {
+        "Nothing" => Ok(PaletteName::Nothing),
+        "BlackToWhiteExp2" => Ok(PaletteName::BlackToWhiteExp2),
+        "BlackToWhiteCircleUp" => Ok(PaletteName::BlackToWhiteCircleUp),
+        "BlackWhiteBlack" => Ok(PaletteName::BlackWhiteBlack),
+        "BlackWBWB" => Ok(PaletteName::BlackWBWB),
+        "BlueToWhiteCircleUp" => Ok(PaletteName::BlueToWhiteCircleUp),
+        "PurpleToWhite" => Ok(PaletteName::PurpleToWhite),
+        "LinearGold" => Ok(PaletteName::LinearGold),
+        "LinearRed" => Ok(PaletteName::LinearRed),
+        "LinearBlue" => Ok(PaletteName::LinearBlue),
+        "LinearGray" => Ok(PaletteName::LinearGray),
+        "NonlinearVolt" => Ok(PaletteName::NonlinearVolt),
+        _ => Err(format!("unknown palette '{}'", text)),
+    }
+}
+
+/**
+ * Flat JSON object with string values, as written by to_json()
+ */
+fn parse_json(json: &str) -> Result<Vec<(String, String)>, String> {
+    let body = json
+        .trim()
+        .strip_prefix('{')
+        .and_then(|b| b.strip_suffix('}'))
+        .ok_or("metadata is not a JSON object")?;
+    let mut chars = body.chars().peekable();
+    let mut entries = Vec::new();
+    loop {
+        skip_whitespace(&mut chars);
+        if chars.peek().is_none() {
+            return Ok(entries);
+        }
+        let key = parse_json_string(&mut chars)?;
+        skip_whitespace(&mut chars);
+        if chars.next() != Some(':') {
+            return Err(format!("expected ':' after '{}'", key));
+        }
+        skip_whitespace(&mut chars);
+        let value = parse_json_string(&mut chars)?;
+        entries.push((key, value));
+        skip_whitespace(&mut chars);
+        match chars.next() {
+            Some(',') | None => {}
+            Some(c) => return Err(format!("unexpected '{}'", c)),
+        }
+    }
+}
+
+fn skip_whitespace(chars: &mut std::iter::Peekable<std::str::Chars>) {
+    while chars.next_if(|c| c.is_whitespace()).is_some() {}
+}
+
+fn parse_json_string(chars: &mut std::iter::Peekable<std::str::Chars>) -> Result<String, String> {
+    if chars.next() != Some('"') {
+        return Err("expected string".to_string());
+    }
+    let mut s = String::new();
+    loop {
+        match chars.next().ok_or("unclosed string")? {
+            '"' => return Ok(s),
+            '\\' => match chars.next().ok_or("unclosed string")? {
+                'n' => s.push('\n'),
+                't' => s.push('\t'),
+                'u' => {
+                    let hex: String = chars.by_ref().take(4).collect();
+                    let code = u32::from_str_radix(&hex, 16).map_err(|_| format!("invalid escape '{}'", hex))?;
+                    s.push(char::from_u32(code).ok_or(format!("invalid escape '{}'", hex))?);
+                }
+                c => s.push(c),
+            },
+            c => s.push(c),
+        }
+    }
+}
+
+fn escape_json(s: &str) -> String {
+    let mut escaped = String::new();
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+pub fn now() -> String {
+    chrono::Local::now().to_rfc3339()
+}
+
+pub fn init_trivial(name: &str, frame: u64) -> Metadata {
+    Metadata {
+        name: name.to_string(),
+        fractal_calc_type: FractalCalculationType::StaticImageMandelbrot,
+        width_x: 2,
+        height_y: 2,
+        center_re: 0.0,
+        center_im: 0.0,
+        width_re: 1.0,
+        iteration_min: 0,
+        iteration_max: 5,
+        orbits: OrbitType::Finite,
+        resolution_multiplier: ResolutionMultiplier::Single,
+        palette: PaletteName::Nothing,
+        palette_zero: PaletteName::Nothing,
+        palette_space: ColorSpace::Srgb,
+        palette_shift: 0.0,
+        coloring_mode: ColoringMode::Perfect,
+        orbit_trap: OrbitTrap::Nothing,
+        orbit_trap_coloring: OrbitTrapColoring::Instead,
+        supersampling: Supersampling::Nothing,
+        spectral_channels: EULER_CHANNELS.to_vec(),
+        fractal_definition: None,
+        frame,
+        render_time: now(),
+        version: VERSION.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::color_space::ColorSpace::OkLch;
+    use crate::color_space::HueDirection::Longer;
+    use crate::coloring_mode::ColoringMode::{Cyclic, Power};
+    use crate::coloring_mode::Cycle;
+    use crate::euler::{Channel, Classifier, Subject, EULER_CHANNELS};
+    use crate::mathematician::Sequence::Lucas;
+    use crate::metadata::{
+        channels_text, escape_json, init_trivial, parse_channels, parse_coloring_mode, parse_json, parse_orbit_trap, parse_palette,
+        parse_supersampling, Metadata,
+    };
+    use crate::orbit_trap::OrbitTrap;
+    use crate::orbit_trap::OrbitTrap::{Cross, Line};
+    use crate::orbit_trap::OrbitTrapColoring::Alongside;
+    use crate::resolution_multiplier::ResolutionMultiplier::Square3;
+    use crate::supersampling::Supersampling::Adaptive;
+    use crate::palettes::PaletteName::File;
+    use std::borrow::Cow;
+
+    #[test]
+    fn test_entries() {
+        let mut m = init_trivial("Fat Star", 3);
+        m.center_re = -0.1;
+        m.palette = File("a.gpl".to_string());
+
+        let entries = m.entries();
+
+        assert_eq!(entries.len(), 24);
+        assert!(entries.contains(&("center_re", "-0.1".to_string())));
+        assert!(entries.contains(&("frame", "3".to_string())));
+        assert!(entries.contains(&("orbits", "Finite".to_string())));
+        assert!(entries.contains(&("palette", "File(\"a.gpl\")".to_string())));
+    }
+
+    #[test]
+    fn test_to_json() {
+        let m = init_trivial("Nebula", 0);
+
+        let json = m.to_json();
+
+        assert!(json.starts_with("{\n  \"name\": \"Nebula\",\n"));
+        assert!(json.contains("  \"resolution_multiplier\": \"Single\",\n"));
+        assert!(json.ends_with("\"\n}\n"));
+    }
+
+    #[test]
+    fn test_from_json() {
+        let mut m = init_trivial("Nebula \"Top\"", 7);
+        m.width_re = 1.0 / 3.0;
+        m.palette_space = OkLch(Longer);
+        m.palette_shift = 0.125;
+        m.coloring_mode = Cyclic(Cycle { period: 12.5, offset: 0.25, mirror: true });
+        m.orbit_trap = Line(-0.5, 1e-9, 0.75);
+        m.orbit_trap_coloring = Alongside;
+        m.supersampling = Adaptive(Square3, 5);
+        m.spectral_channels = vec![
+            Channel {
+                classifiers: vec![Classifier { sequence: Lucas, subject: Subject::Iteration }].into(),
+                color: [0.1, 1.0 / 3.0, 0.0],
+            },
+            Channel { classifiers: Cow::Borrowed(&[]), color: [0.0, 0.0, 1.0] },
+        ];
+        m.fractal_definition = Some("# \"ship\"\nz = abs(z)^2 + c\n".to_string());
+
+        let read = Metadata::from_json(&m.to_json()).unwrap();
+
+        assert_eq!(read.entries(), m.entries());
+        assert_eq!(read.spectral_channels, m.spectral_channels);
+        assert_eq!(read.fractal_definition, m.fractal_definition);
+        assert!(Metadata::from_json("{\"name\": \"Nebula\"}").is_err());
+    }
+
+    #[test]
+    fn test_from_entries_defaults() {
+        let m = init_trivial("Nebula", 7);
+        // metadata of older versions, without coloring settings
+        let entries: Vec<(String, String)> = m.entries()[..13]
+            .iter()
+            .chain(&m.entries()[21..])
+            .map(|(k, v)| (k.to_string(), v.clone()))
+            .collect();
+
+        let read = Metadata::from_entries(&entries).unwrap();
+
+        assert_eq!(read.entries(), m.entries());
+    }
+
+    #[test]
+    fn test_from_entries_invalid() {
+        let mut m = init_trivial("Nebula", 7);
+        for key in ["width_x", "height_y", "iteration_max"] {
+            let entries: Vec<(String, String)> = m
+                .entries()
+                .into_iter()
+                .map(|(k, v)| (k.to_string(), if k == key { "0".to_string() } else { v }))
+                .collect();
+            assert!(Metadata::from_entries(&entries).is_err(), "{}", key);
+        }
+        m.width_re = f64::NAN;
+        assert!(Metadata::from_json(&m.to_json()).is_err());
+        m.width_re = 1.0;
+        m.iteration_min = 10;
+        assert!(Metadata::from_json(&m.to_json()).is_err());
+        m.iteration_min = 0;
+        m.coloring_mode = Cyclic(Cycle { period: 0.0, offset: 0.0, mirror: false });
+        assert!(Metadata::from_json(&m.to_json()).is_err());
+    }
+
+    #[test]
+    fn test_parse_variants() {
+        assert_eq!(parse_coloring_mode("Power(0.5)"), Ok(Power(0.5)));
+        assert!(parse_coloring_mode("Power").is_err());
+        assert_eq!(parse_orbit_trap("Nothing"), Ok(OrbitTrap::Nothing));
+        assert_eq!(parse_orbit_trap("Cross(1.0, 2.0)"), Ok(Cross(1.0, 2.0)));
+        assert!(parse_orbit_trap("Cross(1.0)").is_err());
+        assert!(parse_supersampling("Grid(Square4)").is_err());
+        assert_eq!(parse_channels(&channels_text(EULER_CHANNELS)), Ok(EULER_CHANNELS.to_vec()));
+        assert!(parse_channels("Prime(Index) -> 1.0, 0.0, 0.0").is_err());
+        assert!(parse_channels("Prime(Iteration) -> 1.0, 0.0").is_err());
+    }
+
+    #[test]
+    fn test_parse_json() {
+        let entries = parse_json("{ \"a\": \"1\", \"b\":\"x\\u0041\\\"\" }").unwrap();
+        assert_eq!(entries, vec![("a".to_string(), "1".to_string()), ("b".to_string(), "xA\"".to_string())]);
+        assert!(parse_json("[]").is_err());
+        assert!(parse_json("{\"a\" \"1\"}").is_err());
+    }
+
+    #[test]
+    fn test_parse_palette() {
+        assert!(matches!(parse_palette("LinearGold"), Ok(crate::palettes::PaletteName::LinearGold)));
+        assert!(matches!(parse_palette("File(\"p.gpl\")"), Ok(File(p)) if p == "p.gpl"));
+        assert!(parse_palette("Rainbow").is_err());
+    }
+
+    #[test]
+    fn test_escape_json() {
+        assert_eq!(escape_json("File(\"a\\b.gpl\")"), "File(\\\"a\\\\b.gpl\\\")");
+        assert_eq!(escape_json("a\nb"), "a\\nb");
+    }
+}
@@ -0,0 +1,93 @@
+/**
+ * Orbit trap for Mandelbrot like fractals
+ * - during the calculation, remember how close the calculation path came to the trap
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OrbitTrap {
+    // don't track any distance
+    Nothing,
+    // point [re, im]
+    Point(f64, f64),
+    // line through point [re, im] with angle in radians, 0 is parallel to re axis
+    Line(f64, f64, f64),
+    // circle with center [re, im] and radius
+    Circle(f64, f64, f64),
+    // two lines through point [re, im], parallel to re and im axis
+    Cross(f64, f64),
+}
+
+/**
+ * How to use orbit trap distance when coloring
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OrbitTrapColoring {
+    // color by orbit trap distance instead of iteration value
+    Instead,
+    // mix orbit trap colors with iteration value colors
+    Alongside,
+}
+
+// distance for pixels which didn't track any trap
+pub const NO_TRAP: f64 = f64::MAX;
+
+impl OrbitTrap {
+    pub fn distance(&self, re: f64, im: f64) -> f64 {
+        match *self {
+            OrbitTrap::Nothing => NO_TRAP,
+            OrbitTrap::Point(tre, tim) => {
+                let dr = re - tre;
+                let di = im - tim;
+                (dr * dr + di * di).sqrt()
+            }
+            OrbitTrap::Line(tre, tim, angle) => {
+                ((re - tre) * angle.sin() - (im - tim) * angle.cos()).abs()
+            }
+            OrbitTrap::Circle(tre, tim, radius) => {
+                let dr = re - tre;
+                let di = im - tim;
+                ((dr * dr + di * di).sqrt() - radius).abs()
+            }
+            OrbitTrap::Cross(tre, tim) => (re - tre).abs().min((im - tim).abs()),
+        }
+    }
+
+    pub fn is_trap(&self) -> bool {
+        *self != OrbitTrap::Nothing
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::orbit_trap::OrbitTrap::{Circle, Cross, Line, Nothing, Point};
+    use crate::orbit_trap::NO_TRAP;
+    use std::f64::consts::FRAC_PI_2;
+
+    #[test]
+    fn test_distance_point() {
+        assert_eq!(Point(1.0, 1.0).distance(4.0, 5.0), 5.0);
+    }
+
+    #[test]
+    fn test_distance_line() {
+        assert_eq!(Line(0.0, 1.0, 0.0).distance(7.0, 3.0), 2.0);
+        assert!((Line(1.0, 0.0, FRAC_PI_2).distance(3.0, 7.0) - 2.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_distance_circle() {
+        assert_eq!(Circle(0.0, 0.0, 1.0).distance(3.0, 0.0), 2.0);
+        assert_eq!(Circle(0.0, 0.0, 1.0).distance(0.0, 0.5), 0.5);
+    }
+
+    #[test]
+    fn test_distance_cross() {
+        assert_eq!(Cross(0.0, 0.0).distance(3.0, 0.5), 0.5);
+        assert_eq!(Cross(0.0, 0.0).distance(-0.25, 2.0), 0.25);
+    }
+
+    #[test]
+    fn test_distance_nothing() {
+        assert_eq!(Nothing.distance(0.0, 0.0), NO_TRAP);
+        assert!(!Nothing.is_trap());
+    }
+}
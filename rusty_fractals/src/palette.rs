@@ -17,6 +17,58 @@ impl Palette {
     pub fn spectrum_value(&self, i: usize) -> Rgb<u8> {
         *self.spectrum.get(i).expect(ERROR_MESSAGE)
     }
+
+    /**
+     * Spectrum shifted by fraction of its length, colors from the end continue at the beginning
+     */
+    pub fn rotated(&self, shift: f64) -> Palette {
+        let mut spectrum = self.spectrum.clone();
+        if !spectrum.is_empty() {
+            let length = spectrum.len();
+            let n = (shift.rem_euclid(1.0) * length as f64).round() as usize % length;
+            spectrum.rotate_right(n);
+        }
+        Palette { spectrum }
+    }
+
+    /**
+     * Color at fractional position in spectrum
+     * linear interpolation between the two nearest colors
+     */
+    pub fn spectrum_value_interpolated(&self, position: f64) -> Rgb<u8> {
+        if self.spectrum.is_empty() {
+            return Rgb([0, 0, 0]);
+        }
+        let last = self.spectrum.len() - 1;
+        let position = position.clamp(0.0, last as f64);
+        let i = position.floor() as usize;
+        if i == last {
+            return self.spectrum_value(last);
+        }
+        let t = position - i as f64;
+        let a = self.spectrum_value(i);
+        let b = self.spectrum_value(i + 1);
+        Rgb([
+            interpolate(a[0], b[0], t),
+            interpolate(a[1], b[1], t),
+            interpolate(a[2], b[2], t),
+        ])
+    }
+
+    /**
+     * Color at fraction <0, 1> of spectrum, from the first to the last color
+     */
+    pub fn spectrum_value_at_fraction(&self, t: f64) -> Rgb<u8> {
+        if self.spectrum.is_empty() {
+            return Rgb([0, 0, 0]);
+        }
+        let last = (self.spectrum.len() - 1) as f64;
+        self.spectrum_value_interpolated(t * last)
+    }
+}
+
+fn interpolate(a: u8, b: u8, t: f64) -> u8 {
+    (a as f64 + (b as f64 - a as f64) * t).round() as u8
 }
 
 impl Palette3 {
@@ -41,8 +93,8 @@ pub fn init_trivial() -> Palette {
 
 #[cfg(test)]
 mod tests {
-    use crate::palette::init_trivial;
-    use image::Pixel;
+    use crate::palette::{init_trivial, Palette};
+    use image::{Pixel, Rgb};
 
     #[test]
     fn test_init_default() {
@@ -57,4 +109,39 @@ mod tests {
         let rgb = p.spectrum_value(2);
         assert_eq!(rgb.channels()[0], 0);
     }
+
+    #[test]
+    fn test_rotated() {
+        let p = init_trivial();
+
+        let r = p.rotated(1.0 / 3.0);
+        assert_eq!(r.spectrum_value(0), Rgb([0, 0, 255]));
+        assert_eq!(r.spectrum_value(1), Rgb([255, 0, 0]));
+        assert_eq!(p.rotated(1.0).spectrum, p.spectrum);
+    }
+
+    #[test]
+    fn test_spectrum_value_interpolated() {
+        let p = init_trivial();
+
+        assert_eq!(p.spectrum_value_interpolated(0.0), Rgb([255, 0, 0]));
+        assert_eq!(p.spectrum_value_interpolated(1.25), Rgb([0, 191, 64]));
+        assert_eq!(p.spectrum_value_interpolated(2.0), Rgb([0, 0, 255]));
+        assert_eq!(p.spectrum_value_interpolated(7.0), Rgb([0, 0, 255]));
+
+        let empty = Palette { spectrum: Vec::new() };
+        assert_eq!(empty.spectrum_value_interpolated(1.0), Rgb([0, 0, 0]));
+    }
+
+    #[test]
+    fn test_spectrum_value_at_fraction() {
+        let p = init_trivial();
+
+        assert_eq!(p.spectrum_value_at_fraction(0.0), Rgb([255, 0, 0]));
+        assert_eq!(p.spectrum_value_at_fraction(0.625), Rgb([0, 191, 64]));
+        assert_eq!(p.spectrum_value_at_fraction(1.0), Rgb([0, 0, 255]));
+
+        let empty = Palette { spectrum: Vec::new() };
+        assert_eq!(empty.spectrum_value_at_fraction(0.5), Rgb([0, 0, 0]));
+    }
 }
@@ -0,0 +1,158 @@
+use crate::color_space::{interpolate, ColorSpace};
+use crate::palette::Palette;
+use crate::palette_utils::function_result;
+use crate::palettes::Function;
+use image::Rgb;
+
+/**
+ * Gradient palette with arbitrary number of color stops
+ * - the first stop is at position 0.0, the last one should be at 1.0
+ * - each segment between two stops has its own gradient function
+ */
+pub struct PaletteBuilder {
+    // position, color, function of segment ending at this stop
+    stops: Vec<(f64, Rgb<u8>, Function)>,
+    space: ColorSpace,
+}
+
+impl PaletteBuilder {
+    pub fn new(first: Rgb<u8>) -> Self {
+        PaletteBuilder {
+            stops: vec![(0.0, first, Function::Linear1)],
+            space: ColorSpace::Srgb,
+        }
+    }
+
+    /**
+     * Gradient of two colors, from position 0 to 1
+     */
+    pub fn gradient(from: Rgb<u8>, to: Rgb<u8>, function: Function) -> Self {
+        PaletteBuilder {
+            stops: vec![(0.0, from, Function::Linear1), (1.0, to, function)],
+            space: ColorSpace::Srgb,
+        }
+    }
+
+    /**
+     * Color space in which colors between stops are interpolated
+     */
+    pub fn color_space(mut self, space: ColorSpace) -> Self {
+        self.space = space;
+        self
+    }
+
+    /**
+     * Add color stop at position from <0, 1>
+     * function defines gradient from previous stop to this one
+     * positions must not decrease
+     */
+    pub fn stop(mut self, position: f64, color: Rgb<u8>, function: Function) -> Result<Self, String> {
+        let (previous, _, _) = self.stops[self.stops.len() - 1];
+        if !(previous..=1.0).contains(&position) {
+            return Err(format!("color stop position {} not in <{}, 1>", position, previous));
+        }
+        self.stops.push((position, color, function));
+        Ok(self)
+    }
+
+    /**
+     * Spectrum of exactly length colors
+     */
+    pub fn build(&self, length: usize) -> Palette {
+        let last = (length.max(2) - 1) as f64;
+        let spectrum = (0..length)
+            .map(|i| self.color_at(i as f64 / last))
+            .collect();
+        Palette { spectrum }
+    }
+
+    fn color_at(&self, position: f64) -> Rgb<u8> {
+        // the first stop at or after position
+        let to = match self.stops.iter().position(|(p, _, _)| *p >= position) {
+            None => return self.stops[self.stops.len() - 1].1,
+            Some(0) => return self.stops[0].1,
+            Some(to) => to,
+        };
+        let (p_from, from, _) = self.stops[to - 1];
+        let (p_to, to, function) = self.stops[to];
+        if p_to == p_from {
+            return to;
+        }
+        let d = (position - p_from) / (p_to - p_from);
+        let v = function_result(d, &function).clamp(0.0, 1.0);
+        interpolate(from, to, v, self.space)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::color_space::ColorSpace::OkLab;
+    use crate::palette_builder::PaletteBuilder;
+    use crate::palettes::Function::{Linear1, Quadratic};
+    use image::Rgb;
+
+    const BLACK: Rgb<u8> = Rgb([0, 0, 0]);
+    const WHITE: Rgb<u8> = Rgb([255, 255, 255]);
+
+    #[test]
+    fn test_build_length() {
+        let p = PaletteBuilder::new(BLACK).stop(1.0, WHITE, Linear1).unwrap().build(100);
+        assert_eq!(p.spectrum.len(), 100);
+        assert_eq!(p.spectrum[0], BLACK);
+        assert_eq!(p.spectrum[99], WHITE);
+    }
+
+    #[test]
+    fn test_build_stops() {
+        let p = PaletteBuilder::new(BLACK)
+            .stop(0.5, WHITE, Linear1)
+            .unwrap()
+            .stop(1.0, Rgb([255, 0, 0]), Linear1)
+            .unwrap()
+            .build(5);
+        assert_eq!(p.spectrum[1], Rgb([128, 128, 128]));
+        assert_eq!(p.spectrum[2], WHITE);
+        assert_eq!(p.spectrum[3], Rgb([255, 128, 128]));
+        assert_eq!(p.spectrum[4], Rgb([255, 0, 0]));
+    }
+
+    #[test]
+    fn test_build_function() {
+        let p = PaletteBuilder::new(BLACK)
+            .stop(1.0, Rgb([200, 0, 0]), Quadratic)
+            .unwrap()
+            .build(3);
+        assert_eq!(p.spectrum[1], Rgb([50, 0, 0]));
+    }
+
+    #[test]
+    fn test_build_color_space() {
+        let p = PaletteBuilder::new(BLACK)
+            .stop(1.0, WHITE, Linear1)
+            .unwrap()
+            .color_space(OkLab)
+            .build(3);
+        assert_eq!(p.spectrum[1], Rgb([99, 99, 99]));
+    }
+
+    #[test]
+    fn test_build_last_stop_before_end() {
+        let p = PaletteBuilder::new(BLACK).stop(0.5, WHITE, Linear1).unwrap().build(5);
+        assert_eq!(p.spectrum[3], WHITE);
+        assert_eq!(p.spectrum[4], WHITE);
+    }
+
+    #[test]
+    fn test_stop_order() {
+        let b = PaletteBuilder::new(BLACK).stop(0.5, WHITE, Linear1).unwrap();
+
+        assert!(b.stop(0.2, BLACK, Linear1).is_err());
+        assert!(PaletteBuilder::new(BLACK).stop(1.5, WHITE, Linear1).is_err());
+    }
+
+    #[test]
+    fn test_gradient() {
+        let p = PaletteBuilder::gradient(BLACK, WHITE, Linear1).build(3);
+        assert_eq!(p.spectrum, vec![BLACK, Rgb([128, 128, 128]), WHITE]);
+    }
+}
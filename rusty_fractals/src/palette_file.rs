@@ -0,0 +1,153 @@
+use crate::palette::Palette;
+use image::Rgb;
+use std::fs;
+use std::path::Path;
+
+/**
+ * Load palette from file, by extension
+ * - .gpl  GIMP palette
+ * - .csv  hex colors separated by commas or new lines
+ * - .json array of hex color strings
+ * - .png  image strip, colors of the first pixel row
+ */
+pub fn load_palette(path: &str) -> Result<Palette, String> {
+    let extension = Path::new(path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase())
+        .unwrap_or_default();
+    let spectrum = match extension.as_str() {
+        "gpl" => read(path).and_then(|s| parse_gpl(&s)),
+        "csv" | "json" => read(path).and_then(|s| parse_hex_list(&s)),
+        "png" => read_strip(path),
+        _ => Err(format!("unknown palette file type '{}'", extension)),
+    }
+    .map_err(|e| format!("{}: {}", path, e))?;
+    if spectrum.is_empty() {
+        return Err(format!("{}: no colors in palette", path));
+    }
+    Ok(Palette { spectrum })
+}
+
+fn read(path: &str) -> Result<String, String> {
+    fs::read_to_string(path).map_err(|e| e.to_string())
+}
+
+fn read_strip(path: &str) -> Result<Vec<Rgb<u8>>, String> {
+    let image = image::open(path).map_err(|e| e.to_string())?.to_rgb8();
+    Ok((0..image.width()).map(|x| *image.get_pixel(x, 0)).collect())
+}
+
+/**
+ * GIMP Palette
+ * Name: Example
+ * # comment
+ * 255   0   0  Red
+ */
+fn parse_gpl(content: &str) -> Result<Vec<Rgb<u8>>, String> {
+    let mut lines = content.lines();
+    if lines.next().map(|l| l.trim()) != Some("GIMP Palette") {
+        return Err("missing GIMP Palette header".to_string());
+    }
+    lines
+        .map(|line| line.trim())
+        .filter(|line| {
+            !line.is_empty()
+                && !line.starts_with('#')
+                && !line.starts_with("Name:")
+                && !line.starts_with("Columns:")
+        })
+        .map(|line| {
+            let channels = line
+                .split_whitespace()
+                .take(3)
+                .map(|v| v.parse::<u8>())
+                .collect::<Result<Vec<u8>, _>>()
+                .map_err(|_| format!("invalid color '{}'", line))?;
+            match channels[..] {
+                [r, g, b] => Ok(Rgb([r, g, b])),
+                _ => Err(format!("invalid color '{}'", line)),
+            }
+        })
+        .collect()
+}
+
+/**
+ * Hex colors like #ff8000 or ff8000
+ * separated by commas, white space or new lines, optionally in a JSON array
+ */
+fn parse_hex_list(content: &str) -> Result<Vec<Rgb<u8>>, String> {
+    content
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .map(|token| token.trim_matches(|c| c == '[' || c == ']' || c == '"'))
+        .filter(|token| !token.is_empty())
+        .map(parse_hex)
+        .collect()
+}
+
+fn parse_hex(token: &str) -> Result<Rgb<u8>, String> {
+    let hex = token.trim_start_matches('#');
+    if hex.len() != 6 || !hex.is_ascii() {
+        return Err(format!("invalid hex color '{}'", token));
+    }
+    let channel = |i: usize| {
+        u8::from_str_radix(&hex[i..i + 2], 16).map_err(|_| format!("invalid hex color '{}'", token))
+    };
+    Ok(Rgb([channel(0)?, channel(2)?, channel(4)?]))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::palette_file::{load_palette, parse_gpl, parse_hex, parse_hex_list};
+    use image::{Rgb, RgbImage};
+    use std::env;
+
+    #[test]
+    fn test_parse_gpl() {
+        let content = "GIMP Palette\nName: Test\nColumns: 2\n#\n255   0   0\tRed\n  0 128 255\n";
+        let spectrum = parse_gpl(content).unwrap();
+        assert_eq!(spectrum, vec![Rgb([255, 0, 0]), Rgb([0, 128, 255])]);
+    }
+
+    #[test]
+    fn test_parse_gpl_error() {
+        assert!(parse_gpl("255 0 0").is_err());
+        assert!(parse_gpl("GIMP Palette\n255 0").is_err());
+        assert!(parse_gpl("GIMP Palette\n256 0 0").is_err());
+    }
+
+    #[test]
+    fn test_parse_hex_list() {
+        let csv = parse_hex_list("#ff0000, 00ff00\n#0000FF\n").unwrap();
+        assert_eq!(csv, vec![Rgb([255, 0, 0]), Rgb([0, 255, 0]), Rgb([0, 0, 255])]);
+
+        let json = parse_hex_list("[\"#ff0000\", \"#00ff00\", \"#0000ff\"]").unwrap();
+        assert_eq!(json, csv);
+    }
+
+    #[test]
+    fn test_parse_hex() {
+        assert_eq!(parse_hex("#102030"), Ok(Rgb([16, 32, 48])));
+        assert!(parse_hex("#1020").is_err());
+        assert!(parse_hex("#10203g").is_err());
+    }
+
+    #[test]
+    fn test_load_palette_png() {
+        let path = env::temp_dir().join("rusty_fractals_test_palette.png");
+        let mut strip = RgbImage::new(3, 1);
+        strip.put_pixel(0, 0, Rgb([1, 2, 3]));
+        strip.put_pixel(2, 0, Rgb([7, 8, 9]));
+        strip.save(&path).unwrap();
+
+        let p = load_palette(path.to_str().unwrap()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(p.spectrum, vec![Rgb([1, 2, 3]), Rgb([0, 0, 0]), Rgb([7, 8, 9])]);
+    }
+
+    #[test]
+    fn test_load_palette_error() {
+        assert!(load_palette("missing.gpl").is_err());
+        assert!(load_palette("palette.txt").is_err());
+    }
+}
@@ -147,7 +147,7 @@ pub fn make_spectrum(function: Function, from: Rgb<u8>, to: Rgb<u8>) -> Vec<Rgb<
 // Calculates how much should color in smooth color palette change
 // function : defines gradient of change from color "from" (d=0) to color "to" (d=1)
 // d : 0 <= d <= 1
-fn function_result(d: f64, function: &Function) -> f64 {
+pub fn function_result(d: f64, function: &Function) -> f64 {
     match function {
         Function::Linear1 => d,
         Function::Linear3 => d * 3.0,
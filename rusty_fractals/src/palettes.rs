@@ -1,5 +1,8 @@
+use crate::color_space::ColorSpace;
 use crate::palette::Palette;
 use crate::palette::Palette3;
+use crate::palette_builder::PaletteBuilder;
+use crate::palette_file::load_palette;
 use crate::palette_utils::make_spectrum;
 use crate::palettes::Function::{CircleUp, Exp2, Linear1};
 use image::{Pixel, Rgb};
@@ -11,6 +14,7 @@ const GREEN: Rgb<u8> = Rgb([0, 255, 0]);
 const BLUE: Rgb<u8> = Rgb([0, 0, 255]);
 const GOLD: Rgb<u8> = Rgb([255, 215, 0]);
 
+#[derive(Clone, Copy, Debug)]
 pub enum Function {
     Linear1,
     Linear3,
@@ -22,7 +26,7 @@ pub enum Function {
     CircleUp,
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Debug)]
 pub enum PaletteName {
     Nothing,
     BlackToWhiteExp2,
@@ -36,6 +40,8 @@ pub enum PaletteName {
     LinearBlue,
     LinearGray,
     NonlinearVolt,
+    // .gpl, .csv, .json or .png palette file
+    File(String),
 }
 
 pub fn new<'lt>(function: Function, from: Rgb<u8>, to: Rgb<u8>) -> Palette {
@@ -44,76 +50,112 @@ pub fn new<'lt>(function: Function, from: Rgb<u8>, to: Rgb<u8>) -> Palette {
     }
 }
 
+/**
+ * The same amount of colors as new(), interpolated in the color space
+ */
+pub fn new_in_space(function: Function, from: Rgb<u8>, to: Rgb<u8>, space: ColorSpace) -> Palette {
+    if space == ColorSpace::Srgb {
+        return new(function, from, to);
+    }
+    let length = make_spectrum(function, from, to).len();
+    PaletteBuilder::gradient(from, to, function)
+        .color_space(space)
+        .build(length)
+}
+
 pub fn new_palette_by_name<'lt>(palette_name: &PaletteName) -> Palette {
-    match palette_name {
-        PaletteName::BlackToWhiteExp2 => palette_black_to_white_exp2(),
-        PaletteName::BlackToWhiteCircleUp => palette_black_to_white_circle_up(),
-        PaletteName::BlackWhiteBlack => palette_black_white_black(),
-        PaletteName::BlackWBWB => palette_bwbwb(),
-        PaletteName::BlueToWhiteCircleUp => palette_blue_to_white_circle_up(),
-        PaletteName::PurpleToWhite => palette_purple_to_white(),
-        PaletteName::LinearGold => palette_linear_gold(),
-        PaletteName::LinearRed => palette_linear_red(),
-        PaletteName::LinearBlue => palette_linear_blue(),
-        PaletteName::LinearGray => palette_linear_gray(),
-        PaletteName::NonlinearVolt => palette_nonlinear_volt(),
+    new_palette_by_name_in_space(palette_name, ColorSpace::Srgb)
+}
+
+/**
+ * Named palette with colors interpolated in the color space
+ * Palette file which can't be loaded is reported, default palette is used instead.
+ */
+pub fn new_palette_by_name_in_space(palette_name: &PaletteName, space: ColorSpace) -> Palette {
+    load_palette_by_name_in_space(palette_name, space).unwrap_or_else(|e| {
+        println!("palette error: {}", e);
+        palette_linear_gray(space)
+    })
+}
+
+/**
+ * Named palette, error if palette file can't be loaded
+ */
+pub fn load_palette_by_name_in_space(
+    palette_name: &PaletteName,
+    space: ColorSpace,
+) -> Result<Palette, String> {
+    let palette = match palette_name {
+        PaletteName::BlackToWhiteExp2 => palette_black_to_white_exp2(space),
+        PaletteName::BlackToWhiteCircleUp => palette_black_to_white_circle_up(space),
+        PaletteName::BlackWhiteBlack => palette_black_white_black(space),
+        PaletteName::BlackWBWB => palette_bwbwb(space),
+        PaletteName::BlueToWhiteCircleUp => palette_blue_to_white_circle_up(space),
+        PaletteName::PurpleToWhite => palette_purple_to_white(space),
+        PaletteName::LinearGold => palette_linear_gold(space),
+        PaletteName::LinearRed => palette_linear_red(space),
+        PaletteName::LinearBlue => palette_linear_blue(space),
+        PaletteName::LinearGray => palette_linear_gray(space),
+        PaletteName::NonlinearVolt => palette_nonlinear_volt(space),
         PaletteName::Nothing => init_trivial(),
-    }
+        PaletteName::File(path) => load_palette(path)?,
+    };
+    Ok(palette)
 }
 
-pub fn palette_black_to_white_exp2<'lt>() -> Palette {
-    new(Exp2, BLACK, WHITE)
+pub fn palette_black_to_white_exp2<'lt>(space: ColorSpace) -> Palette {
+    new_in_space(Exp2, BLACK, WHITE, space)
 }
 
-pub fn palette_black_to_white_circle_up<'lt>() -> Palette {
-    new(CircleUp, BLACK, WHITE)
+pub fn palette_black_to_white_circle_up<'lt>(space: ColorSpace) -> Palette {
+    new_in_space(CircleUp, BLACK, WHITE, space)
 }
 
-pub fn palette_black_white_black<'lt>() -> Palette {
-    let mut black_to_white_work = make_spectrum(Linear1, BLACK, WHITE);
-    let mut white_to_black = make_spectrum(Linear1, WHITE, BLACK);
+pub fn palette_black_white_black<'lt>(space: ColorSpace) -> Palette {
+    let mut black_to_white_work = new_in_space(Linear1, BLACK, WHITE, space).spectrum;
+    let mut white_to_black = new_in_space(Linear1, WHITE, BLACK, space).spectrum;
     black_to_white_work.append(&mut white_to_black);
     Palette {
         spectrum: black_to_white_work,
     }
 }
 
-pub fn palette_bwbwb<'lt>() -> Palette {
-    let mut bwbwb_work = make_spectrum(Linear1, BLACK, WHITE);
-    bwbwb_work.append(&mut make_spectrum(Linear1, WHITE, BLACK));
-    bwbwb_work.append(&mut make_spectrum(Linear1, BLACK, WHITE));
-    bwbwb_work.append(&mut make_spectrum(Linear1, WHITE, BLACK));
+pub fn palette_bwbwb<'lt>(space: ColorSpace) -> Palette {
+    let mut bwbwb_work = new_in_space(Linear1, BLACK, WHITE, space).spectrum;
+    bwbwb_work.append(&mut new_in_space(Linear1, WHITE, BLACK, space).spectrum);
+    bwbwb_work.append(&mut new_in_space(Linear1, BLACK, WHITE, space).spectrum);
+    bwbwb_work.append(&mut new_in_space(Linear1, WHITE, BLACK, space).spectrum);
     Palette {
         spectrum: bwbwb_work,
     }
 }
 
-pub fn palette_blue_to_white_circle_up<'lt>() -> Palette {
-    new(CircleUp, Rgb([4, 13, 33]), WHITE)
+pub fn palette_blue_to_white_circle_up<'lt>(space: ColorSpace) -> Palette {
+    new_in_space(CircleUp, Rgb([4, 13, 33]), WHITE, space)
 }
 
-pub fn palette_linear_blue<'lt>() -> Palette {
-    new(Linear1, Rgb([104, 113, 133]), Rgb([4, 13, 33]))
+pub fn palette_linear_blue<'lt>(space: ColorSpace) -> Palette {
+    new_in_space(Linear1, Rgb([104, 113, 133]), Rgb([4, 13, 33]), space)
 }
 
-pub fn palette_linear_gray<'lt>() -> Palette {
-    new(Linear1, Rgb([100, 100, 100]), Rgb([0, 0, 0]))
+pub fn palette_linear_gray<'lt>(space: ColorSpace) -> Palette {
+    new_in_space(Linear1, Rgb([100, 100, 100]), Rgb([0, 0, 0]), space)
 }
 
-pub fn palette_nonlinear_volt<'lt>() -> Palette {
-    new(CircleUp, Rgb([32, 15, 48]), WHITE)
+pub fn palette_nonlinear_volt<'lt>(space: ColorSpace) -> Palette {
+    new_in_space(CircleUp, Rgb([32, 15, 48]), WHITE, space)
 }
 
-pub fn palette_purple_to_white<'lt>() -> Palette {
-    new(CircleUp, Rgb([20, 3, 30]), WHITE)
+pub fn palette_purple_to_white<'lt>(space: ColorSpace) -> Palette {
+    new_in_space(CircleUp, Rgb([20, 3, 30]), WHITE, space)
 }
 
-pub fn palette_linear_gold<'lt>() -> Palette {
-    new(Linear1, Rgb([4, 13, 33]), GOLD)
+pub fn palette_linear_gold<'lt>(space: ColorSpace) -> Palette {
+    new_in_space(Linear1, Rgb([4, 13, 33]), GOLD, space)
 }
 
-pub fn palette_linear_red<'lt>() -> Palette {
-    new(Linear1, BLACK, RED)
+pub fn palette_linear_red<'lt>(space: ColorSpace) -> Palette {
+    new_in_space(Linear1, BLACK, RED, space)
 }
 
 pub fn init_trivial<'lt>() -> Palette {
@@ -138,7 +180,12 @@ fn strip_spectrum(spectrum: Vec<Rgb<u8>>, channel: usize) -> Vec<u8> {
 
 #[cfg(test)]
 mod tests {
-    use crate::palettes::new;
+    use crate::color_space::ColorSpace::{OkLab, Srgb};
+    use crate::palettes::PaletteName::{File, LinearRed};
+    use crate::palettes::{
+        load_palette_by_name_in_space, new, new_in_space, new_palette_by_name, new_palette_by_name_in_space,
+        palette_black_white_black, palette_bwbwb,
+    };
     use crate::palettes::Function::Linear1;
     use image::Rgb;
 
@@ -147,4 +194,50 @@ mod tests {
         let n = new(Linear1, Rgb([100, 100, 100]), Rgb([103, 103, 103]));
         assert_eq!(n.spectrum.len(), 4);
     }
+
+    #[test]
+    fn test_new_palette_by_name() {
+        assert_eq!(new_palette_by_name(&LinearRed).spectrum.len(), 256);
+        assert_eq!(new_palette_by_name(&LinearRed).spectrum[255], Rgb([255, 0, 0]));
+
+        // missing file reported, gray palette used
+        let p = new_palette_by_name_in_space(&File("missing.gpl".to_string()), Srgb);
+        assert_eq!(p.spectrum[0], Rgb([100, 100, 100]));
+    }
+
+    #[test]
+    fn test_load_palette_by_name_in_space() {
+        assert!(load_palette_by_name_in_space(&LinearRed, Srgb).is_ok());
+        assert!(load_palette_by_name_in_space(&File("missing.gpl".to_string()), Srgb).is_err());
+    }
+
+    #[test]
+    fn test_new_in_space() {
+        let n = new_in_space(Linear1, Rgb([0, 0, 0]), Rgb([255, 255, 255]), OkLab);
+        assert_eq!(n.spectrum.len(), 256);
+        assert_eq!(n.spectrum[0], Rgb([0, 0, 0]));
+        assert_eq!(n.spectrum[255], Rgb([255, 255, 255]));
+        // perceptually uniform, darker midtones than sRGB
+        assert!(n.spectrum[128][0] < 128);
+    }
+
+    #[test]
+    fn test_palette_black_white_black() {
+        let p = palette_black_white_black(Srgb);
+        assert_eq!(p.spectrum.len(), 512);
+        assert_eq!(p.spectrum[100], Rgb([100, 100, 100]));
+        assert_eq!(p.spectrum[255], Rgb([255, 255, 255]));
+        assert_eq!(p.spectrum[256], Rgb([255, 255, 255]));
+        assert_eq!(p.spectrum[411], Rgb([100, 100, 100]));
+    }
+
+    #[test]
+    fn test_palette_bwbwb() {
+        let p = palette_bwbwb(Srgb);
+        assert_eq!(p.spectrum.len(), 1024);
+        assert_eq!(p.spectrum[511], Rgb([0, 0, 0]));
+        assert_eq!(p.spectrum[512], Rgb([0, 0, 0]));
+        assert_eq!(p.spectrum[767], Rgb([255, 255, 255]));
+        assert_eq!(p.spectrum[1023], Rgb([0, 0, 0]));
+    }
 }
@@ -23,155 +23,80 @@ impl Pix {
 
 #[rustfmt::skip]
 pub fn perfectly_color_euler_values(data: &DataImage) {
-    // todo
-    
+    println!("perfectly_color_euler_values()");
+
     let width = data.width_xp;
     let height = data.height_yp;
 
+    // each spectral channel is colored by intensity, the color matrix mixes them later
     let palette3: Palette3 = palette_3_rgb();
-
-    // Result pixels, order by value
-    let mut pixels_red: Vec<Pix> = Vec::new();
-    let mut pixels_green: Vec<Pix> = Vec::new();
-    let mut pixels_blue: Vec<Pix> = Vec::new();
-
-    let mut zero_value_elements_red = 0;
-    let mut zero_value_elements_green = 0;
-    let mut zero_value_elements_blue = 0;
-
-    // identify zero and low-value elements as zero or noise
-    let threshold = 1;
-
-    // read screen values
-    for y in 0..height {
-        for x in 0..width {
-            let (r, g, b) = data.value_at3(x, y);
-
-            if r <= threshold {
-                zero_value_elements_red += 1;
-            }
-            if g <= threshold {
-                zero_value_elements_green += 1;
+    let intensities = &palette3.spectrum_red;
+
+    for channel in 0..data.spectral_channels.len() {
+        // Result pixels, order by value
+        let mut pixels: Vec<Pix> = Vec::new();
+        let mut zero_value_elements = 0;
+
+        // identify zero and low-value elements as zero or noise
+        let threshold = 1;
+
+        // read screen values
+        for y in 0..height {
+            for x in 0..width {
+                let value = data.value_at_channel(x, y, channel);
+                if value <= threshold {
+                    zero_value_elements += 1;
+                }
+                pixels.push(Pix { x, y, value });
             }
-            if b <= threshold {
-                zero_value_elements_blue += 1;
-            }
-            pixels_red.push(Pix { x, y, value: r });
-            pixels_green.push(Pix { x, y, value: g });
-            pixels_blue.push(Pix { x, y, value: b });
         }
-    }
 
-    // order pixels from the smallest to the highest value
-    pixels_red.sort_by(|first, second| first.value.cmp(&second.value));
-    pixels_green.sort_by(|first, second| first.value.cmp(&second.value));
-    pixels_blue.sort_by(|first, second| first.value.cmp(&second.value));
-
-    let all_pixels_total: u64 = width as u64 * height as u64;
-    let all_pixels_non_zero_red: u64 = all_pixels_total - zero_value_elements_red;
-    let all_pixels_non_zero_green: u64 = all_pixels_total - zero_value_elements_green;
-    let all_pixels_non_zero_blue: u64 = all_pixels_total - zero_value_elements_blue;
-
-    let palette_color_count: u64 = palette3.spectrum_red.len() as u64;
-    assert_eq!(palette3.spectrum_red.len(), palette3.spectrum_blue.len());
-    assert_eq!(palette3.spectrum_red.len(), palette3.spectrum_green.len());
-
-    let single_color_use_red: u64 = all_pixels_non_zero_red / palette_color_count;
-    let single_color_use_green: u64 = all_pixels_non_zero_green / palette_color_count;
-    let single_color_use_blue: u64 = all_pixels_non_zero_blue / palette_color_count;
-    let left_red: u64 = all_pixels_non_zero_red - (palette_color_count * single_color_use_red);
-    let left_green: u64 = all_pixels_non_zero_green - (palette_color_count * single_color_use_green);
-    let left_blue: u64 = all_pixels_non_zero_blue - (palette_color_count * single_color_use_blue);
-
-    println!("------------------------------------");
-    println!("All pixels to paint:        {:8}", all_pixels_total);
-    println!("--------------------------> {:8}", zero_value_elements_red + left_red + (single_color_use_red * palette_color_count));
-    println!("--------------------------> {:8}", zero_value_elements_green + left_green + (single_color_use_green * palette_color_count));
-    println!("--------------------------> {:8}", zero_value_elements_blue + left_blue + (single_color_use_blue * palette_color_count));
-    println!("Zero value pixels to paint: {:8}", zero_value_elements_red);
-    println!("Zero value pixels to paint: {:8}", zero_value_elements_green);
-    println!("Zero value pixels to paint: {:8}", zero_value_elements_blue);
-    println!("Non zero pixels to paint:   {:8}", all_pixels_non_zero_red);
-    println!("Non zero pixels to paint:   {:8}", all_pixels_non_zero_green);
-    println!("Non zero pixels to paint:   {:8}", all_pixels_non_zero_blue);
-    println!("Spectrum, available colors: {:8}", palette_color_count);
-    println!("Pixels per each color:      {:8}", single_color_use_red);
-    println!("Pixels per each color:      {:8}", single_color_use_green);
-    println!("Pixels per each color:      {:8}", single_color_use_blue);
-    println!("left:                       {:8}", left_red);
-    println!("left:                       {:8}", left_green);
-    println!("left:                       {:8}", left_blue);
-    println!("------------------------------------");
-
-    // paint mismatched pixel amount with the least value color
-    let mut pi_red = 0;
-    while pi_red < (left_red + zero_value_elements_red) as usize {
-        let sp = pixels_red.get(pi_red).unwrap();
-        data.color_r(sp.x, sp.y, palette3.spectrum_value_red(0));
-        pi_red += 1;
-    }
-    // color all remaining pixels, these are order by value
-    for palette_color_index in 0..palette_color_count as usize {
-        for _ in 0..single_color_use_red {
-            // color all these pixels with same color
-            pi_red += 1;
-            let sp = pixels_red.get(pi_red).unwrap();
-            if sp.value() <= threshold {
-                data.color_r(sp.x, sp.y, palette3.spectrum_value_red(0));
-            } else {
-                // perfect-color all significant pixels
-                data.color_r(sp.x, sp.y, palette3.spectrum_value_red(palette_color_index));
-            }
+        // order pixels from the smallest to the highest value
+        pixels.sort_by(|first, second| first.value.cmp(&second.value));
+
+        let all_pixels_total: u64 = width as u64 * height as u64;
+        let all_pixels_non_zero: u64 = all_pixels_total - zero_value_elements;
+        let palette_color_count: u64 = intensities.len() as u64;
+        let single_color_use: u64 = all_pixels_non_zero / palette_color_count;
+        let left: u64 = all_pixels_non_zero - (palette_color_count * single_color_use);
+
+        println!("------------------------------------");
+        println!("Spectral channel:           {:8}", channel);
+        println!("All pixels to paint:        {:8}", all_pixels_total);
+        println!("--------------------------> {:8}", zero_value_elements + left + (single_color_use * palette_color_count));
+        println!("Zero value pixels to paint: {:8}", zero_value_elements);
+        println!("Non zero pixels to paint:   {:8}", all_pixels_non_zero);
+        println!("Spectrum, available colors: {:8}", palette_color_count);
+        println!("Pixels per each color:      {:8}", single_color_use);
+        println!("left:                       {:8}", left);
+        println!("------------------------------------");
+
+        // paint mismatched pixel amount with the least value color
+        let mut pi = 0;
+        while pi < (left + zero_value_elements) as usize {
+            let sp = pixels.get(pi).unwrap();
+            data.color_channel(sp.x, sp.y, channel, intensities[0]);
+            pi += 1;
         }
-    }
-
-    let mut pi_green = 0;
-    while pi_green < (left_green + zero_value_elements_green) as usize {
-        let sp = pixels_green.get(pi_green).unwrap();
-        data.color_g(sp.x, sp.y, palette3.spectrum_value_green(0));
-
-        pi_green += 1;
-    }
-    // color all remaining pixels, these are order by value
-    for palette_color_index in 0..palette_color_count as usize {
-        for _ in 0..single_color_use_green {
-            // color all these pixels with same color
-            pi_green += 1;
-            let sp = pixels_green.get(pi_green).unwrap();
-            if sp.value() <= threshold {
-                // color zero-value elements and low-value-noise with the darkest color
-                data.color_g(sp.x, sp.y, palette3.spectrum_value_green(0));
-            } else {
-                // perfect-color all significant pixels
-                data.color_g(sp.x, sp.y, palette3.spectrum_value_green(palette_color_index));
+        // color all remaining pixels, these are order by value
+        for palette_color_index in 0..palette_color_count as usize {
+            for _ in 0..single_color_use {
+                // color all these pixels with same color
+                let sp = pixels.get(pi).unwrap();
+                pi += 1;
+                if sp.value() <= threshold {
+                    // color zero-value elements and low-value-noise with the darkest color
+                    data.color_channel(sp.x, sp.y, channel, intensities[0]);
+                } else {
+                    // perfect-color all significant pixels
+                    data.color_channel(sp.x, sp.y, channel, intensities[palette_color_index]);
+                }
             }
         }
+        assert_eq!(pixels.len(), pi);
     }
 
-    let mut pi_blue = 0;
-    while pi_blue < (left_blue + zero_value_elements_blue) as usize {
-        let sp = pixels_blue.get(pi_blue).unwrap();
-        data.color_b(sp.x, sp.y, palette3.spectrum_value_blue(0));
-
-        pi_blue += 1;
-    }
-    // color all remaining pixels, these are order by value
-    for palette_color_index in 0..palette_color_count as usize {
-        for _ in 0..single_color_use_blue {
-            // color all these pixels with same color
-            pi_blue += 1;
-            let sp = pixels_blue.get(pi_blue).unwrap();
-            if sp.value() <= threshold {
-                // color zero-value elements and low-value-noise with the darkest color
-                data.color_b(sp.x, sp.y, palette3.spectrum_value_blue(0));
-            } else {
-                // perfect-color all significant pixels
-                data.color_b(sp.x, sp.y, palette3.spectrum_value_blue(palette_color_index));
-            }
-        }
-    }
-
-    // read 3 euler spectra colors and write image colors
+    // read euler spectral channel colors and write image colors
     for y in 0..height {
         for x in 0..width {
             data.define_color_at3(x, y);
@@ -182,8 +107,38 @@ pub fn perfectly_color_euler_values(data: &DataImage) {
 
 #[cfg(test)]
 mod tests {
+    use crate::area;
+    use crate::data_image::init;
+    use crate::fractal::init_trivial_dynamic_config;
+    use crate::fractal::FractalCalculationType::StaticSpectralImageEuler;
+    use crate::mathematician;
+    use crate::perfect_color_distribution_euler::perfectly_color_euler_values;
+    use image::Rgb;
+
     #[test]
     fn test_perfectly_color_euler_values() {
-        // TODO
+        // enough pixels for all 256 colors of each spectrum
+        let mut c = init_trivial_dynamic_config(21);
+        c.fractal_calc_type = StaticSpectralImageEuler;
+        let a = area::init(&c);
+        let di = init(&c, &a);
+        mathematician::init_primes(10);
+        mathematician::init_fibonacci(10);
+
+        // each pixel gets more paths than the previous one
+        for y in 0..21 {
+            for x in 0..21 {
+                let (re, im) = di.origin_at(x, y);
+                for _ in 0..=(x + 21 * y) {
+                    di.translate_one_path_to_spectra_now(vec![[re, im]; 8], (1..=8).collect(), &a);
+                }
+            }
+        }
+        perfectly_color_euler_values(&di);
+
+        assert_eq!(di.color_at(0, 0), Some(Rgb([0, 0, 0])));
+        assert_eq!(di.color_at(20, 20), Some(Rgb([255, 255, 255])));
+        let c = di.color_at(10, 10).unwrap();
+        assert!(c[0] > 0 && c[0] < 255);
     }
 }
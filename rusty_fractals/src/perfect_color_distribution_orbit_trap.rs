@@ -0,0 +1,144 @@
+// The method used for perfect coloring by orbit trap is
+// - Gather all screen pixels and order them by distance to the orbit trap
+// - Count how many pixels should be colored by each color from spectrum
+// - The farthest pixels color by the lowest color, the closest by the highest
+// - Mandelbrot set insides color by zero palette, the same way
+
+use crate::data_image::DataImage;
+use crate::orbit_trap::OrbitTrapColoring;
+use crate::palette::Palette;
+use image::Rgb;
+
+// for Mandelbrot like fractals
+struct Trap {
+    x: usize,
+    y: usize,
+    // the closest distance of calculation path to orbit trap
+    trap: f64,
+}
+
+pub fn perfectly_color_orbit_trap_values(
+    data: &DataImage,
+    palette: &Palette,
+    palette_zero: &Palette,
+    coloring: OrbitTrapColoring,
+) {
+    println!("perfectly_color_orbit_trap_values()");
+
+    let mut pixels: Vec<Trap> = Vec::new();
+    let mut pixels_zero: Vec<Trap> = Vec::new();
+
+    for y in 0..data.height_yp {
+        for x in 0..data.width_xp {
+            let (value, trap) = data.value_trap_at(x, y);
+            if value == 0 {
+                pixels_zero.push(Trap { x, y, trap });
+            } else {
+                pixels.push(Trap { x, y, trap });
+            }
+        }
+    }
+
+    color_by_trap(data, pixels, palette, coloring);
+    // Paint insides of Mandelbrot set
+    color_by_trap(data, pixels_zero, palette_zero, coloring);
+}
+
+fn color_by_trap(
+    data: &DataImage,
+    mut pixels: Vec<Trap>,
+    palette: &Palette,
+    coloring: OrbitTrapColoring,
+) {
+    let palette_color_count = palette.spectrum.len();
+    if palette_color_count == 0 {
+        return;
+    }
+
+    // order pixels from the farthest to the closest
+    pixels.sort_by(|first, second| second.trap.total_cmp(&first.trap));
+
+    let single_color_use = pixels.len() / palette_color_count;
+    let left = pixels.len() - (palette_color_count * single_color_use);
+
+    for (pi, tp) in pixels.iter().enumerate() {
+        // paint mismatched pixel amount with the least value color
+        let palette_color_index = if pi < left {
+            0
+        } else {
+            (pi - left) / single_color_use
+        };
+        let trap_color = palette.spectrum_value(palette_color_index);
+        let color = match (coloring, data.color_at(tp.x, tp.y)) {
+            (OrbitTrapColoring::Alongside, Some(value_color)) => mix(value_color, trap_color),
+            _ => trap_color,
+        };
+        data.color(tp.x, tp.y, color);
+    }
+}
+
+fn mix(a: Rgb<u8>, b: Rgb<u8>) -> Rgb<u8> {
+    Rgb([
+        ((a[0] as u16 + b[0] as u16) / 2) as u8,
+        ((a[1] as u16 + b[1] as u16) / 2) as u8,
+        ((a[2] as u16 + b[2] as u16) / 2) as u8,
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::area;
+    use crate::data_image::init;
+    use crate::fractal::init_trivial_dynamic_config;
+    use crate::orbit_trap::OrbitTrapColoring::{Alongside, Instead};
+    use crate::palette::init_trivial;
+    use crate::perfect_color_distribution_orbit_trap::{mix, perfectly_color_orbit_trap_values};
+    use crate::pixel_states::DomainElementState::FinishedSuccess;
+    use image::Rgb;
+
+    #[test]
+    fn test_perfectly_color_orbit_trap_values() {
+        let c = init_trivial_dynamic_config(3);
+        let a = area::init(&c);
+        let di = init(&c, &a);
+        let p = init_trivial();
+
+        // 9 pixels, 3 colors, the closest pixels get the last color
+        for y in 0..3 {
+            for x in 0..3 {
+                let trap = (x + 3 * y) as f64;
+                di.set_pixel_mandelbrot(x, y, 5, 0.0, FinishedSuccess, 10);
+                di.set_pixel_trap(x, y, trap);
+            }
+        }
+        perfectly_color_orbit_trap_values(&di, &p, &p, Instead);
+
+        assert_eq!(di.color_at(0, 0), Some(Rgb([0, 0, 255])));
+        assert_eq!(di.color_at(1, 1), Some(Rgb([0, 255, 0])));
+        assert_eq!(di.color_at(2, 2), Some(Rgb([255, 0, 0])));
+    }
+
+    #[test]
+    fn test_alongside() {
+        let c = init_trivial_dynamic_config(3);
+        let a = area::init(&c);
+        let di = init(&c, &a);
+        let p = init_trivial();
+
+        for y in 0..3 {
+            for x in 0..3 {
+                di.set_pixel_mandelbrot(x, y, 5, 0.0, FinishedSuccess, 10);
+                di.set_pixel_trap(x, y, 1.0);
+                di.color(x, y, Rgb([255, 255, 255]));
+            }
+        }
+        perfectly_color_orbit_trap_values(&di, &p, &p, Alongside);
+
+        assert_eq!(di.color_at(0, 0), Some(Rgb([255, 127, 127])));
+    }
+
+    #[test]
+    fn test_mix() {
+        assert_eq!(mix(Rgb([0, 10, 255]), Rgb([255, 20, 255])), Rgb([127, 15, 255]));
+    }
+}
@@ -0,0 +1,386 @@
+use crate::fractal::{FractalMath, MemType};
+use crate::mathematician::multiply_by;
+use crate::mem::Mem;
+use std::fmt;
+use std::fs;
+use std::str::FromStr;
+
+/**
+ * Complex polynomial
+ * a_0 + a_1 z + a_2 z^2 + ... + a_n z^n
+ */
+pub struct Polynomial {
+    // [re, im] of a_0, a_1, ... a_n
+    pub coefficients: Vec<[f64; 2]>,
+}
+
+impl Polynomial {
+    pub fn new(coefficients: Vec<[f64; 2]>) -> Self {
+        Polynomial { coefficients }
+    }
+
+    /**
+     * z^n
+     */
+    pub fn power(n: usize) -> Self {
+        let mut coefficients = vec![[0.0, 0.0]; n + 1];
+        coefficients[n] = [1.0, 0.0];
+        Polynomial { coefficients }
+    }
+
+    pub fn degree(&self) -> usize {
+        self.coefficients
+            .iter()
+            .rposition(|a| a[0] != 0.0 || a[1] != 0.0)
+            .unwrap_or(0)
+    }
+
+    /**
+     * Horner's scheme
+     * (((a_n z + a_n-1) z + a_n-2) z + ... ) z + a_0
+     */
+    pub fn evaluate(&self, re: f64, im: f64) -> Mem {
+        let mut r = Mem::new(0.0, 0.0);
+        for a in self.coefficients.iter().rev() {
+            multiply_by(&mut r, re, im);
+            r.plus(a[0], a[1]);
+        }
+        r
+    }
+}
+
+impl FromStr for Polynomial {
+    type Err = String;
+
+    /**
+     * Coefficients a_0 a_1 ... a_n separated by white space
+     * complex coefficient as re,im
+     */
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let coefficients = s
+            .split_whitespace()
+            .map(parse_coefficient)
+            .collect::<Result<Vec<[f64; 2]>, String>>()?;
+        if coefficients.is_empty() {
+            return Err(format!("no coefficients in '{}'", s));
+        }
+        Ok(Polynomial { coefficients })
+    }
+}
+
+impl fmt::Display for Polynomial {
+    /**
+     * Inverse of from_str(), f64 values are printed exactly
+     */
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let coefficients: Vec<String> = self
+            .coefficients
+            .iter()
+            .map(|a| format!("{},{}", a[0], a[1]))
+            .collect();
+        write!(f, "{}", coefficients.join(" "))
+    }
+}
+
+/**
+ * Polynomial fractal
+ * - for each step: z := P(z) + c
+ * - more steps make composition of maps, like f(f(z))
+ */
+pub struct PolynomialFractal {
+    pub steps: Vec<Polynomial>,
+}
+
+impl PolynomialFractal {
+    pub fn new(steps: Vec<Polynomial>) -> Self {
+        PolynomialFractal { steps }
+    }
+
+    /**
+     * z := z^n + c
+     */
+    pub fn power(n: usize) -> Self {
+        PolynomialFractal {
+            steps: vec![Polynomial::power(n)],
+        }
+    }
+
+    /**
+     * Apply steps of other fractal after the steps of this one
+     */
+    pub fn compose(mut self, other: PolynomialFractal) -> Self {
+        self.steps.extend(other.steps);
+        self
+    }
+
+    /**
+     * Degree of the whole iterated map
+     */
+    pub fn degree(&self) -> usize {
+        self.steps.iter().map(|p| p.degree()).product()
+    }
+
+    pub fn from_file(path: &str) -> Result<Self, String> {
+        let content = fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+        content.parse()
+    }
+}
+
+impl FromStr for PolynomialFractal {
+    type Err = String;
+
+    /**
+     * One polynomial per line, lines starting with # are ignored
+     */
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let steps = config_lines(s)
+            .map(|line| line.parse())
+            .collect::<Result<Vec<Polynomial>, String>>()?;
+        if steps.is_empty() {
+            return Err("no polynomial defined".to_string());
+        }
+        Ok(PolynomialFractal { steps })
+    }
+}
+
+impl fmt::Display for PolynomialFractal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for p in &self.steps {
+            writeln!(f, "{}", p)?;
+        }
+        Ok(())
+    }
+}
+
+impl FractalMath<Mem> for PolynomialFractal {
+    fn math(&self, m: &mut Mem, origin_re: f64, origin_im: f64) {
+        for p in &self.steps {
+            let r = p.evaluate(m.re, m.im);
+            m.re = r.re;
+            m.im = r.im;
+            m.plus(origin_re, origin_im);
+        }
+    }
+
+    fn degree(&self) -> f64 {
+        PolynomialFractal::degree(self) as f64
+    }
+
+    fn definition(&self) -> Option<String> {
+        Some(self.to_string())
+    }
+}
+
+/**
+ * Rational map fractal
+ * - for each step: z := N(z) / D(z) + c
+ */
+pub struct RationalFractal {
+    pub steps: Vec<(Polynomial, Polynomial)>,
+}
+
+impl RationalFractal {
+    pub fn new(numerator: Polynomial, denominator: Polynomial) -> Self {
+        RationalFractal {
+            steps: vec![(numerator, denominator)],
+        }
+    }
+
+    pub fn compose(mut self, other: RationalFractal) -> Self {
+        self.steps.extend(other.steps);
+        self
+    }
+
+    /**
+     * Degree of the whole iterated map at infinity
+     */
+    pub fn degree(&self) -> usize {
+        self.steps
+            .iter()
+            .map(|(n, d)| n.degree().saturating_sub(d.degree()))
+            .product()
+    }
+
+    pub fn from_file(path: &str) -> Result<Self, String> {
+        let content = fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+        content.parse()
+    }
+}
+
+impl FromStr for RationalFractal {
+    type Err = String;
+
+    /**
+     * One map per line as: numerator / denominator
+     * Denominator 1 may be omitted
+     */
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let steps = config_lines(s)
+            .map(|line| match line.split_once('/') {
+                Some((n, d)) => Ok((n.parse()?, d.parse()?)),
+                None => Ok((line.parse()?, Polynomial::power(0))),
+            })
+            .collect::<Result<Vec<(Polynomial, Polynomial)>, String>>()?;
+        if steps.is_empty() {
+            return Err("no rational map defined".to_string());
+        }
+        Ok(RationalFractal { steps })
+    }
+}
+
+impl fmt::Display for RationalFractal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (numerator, denominator) in &self.steps {
+            writeln!(f, "{} / {}", numerator, denominator)?;
+        }
+        Ok(())
+    }
+}
+
+impl FractalMath<Mem> for RationalFractal {
+    fn math(&self, m: &mut Mem, origin_re: f64, origin_im: f64) {
+        for (numerator, denominator) in &self.steps {
+            let mut n = numerator.evaluate(m.re, m.im);
+            let d = denominator.evaluate(m.re, m.im);
+            // N / D = N * conj(D) / |D|^2
+            let q = d.quad();
+            multiply_by(&mut n, d.re / q, -d.im / q);
+            m.re = n.re;
+            m.im = n.im;
+            m.plus(origin_re, origin_im);
+        }
+    }
+
+    fn degree(&self) -> f64 {
+        RationalFractal::degree(self) as f64
+    }
+
+    fn definition(&self) -> Option<String> {
+        Some(self.to_string())
+    }
+}
+
+fn config_lines(s: &str) -> impl Iterator<Item = &str> {
+    s.lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+}
+
+fn parse_coefficient(s: &str) -> Result<[f64; 2], String> {
+    let parse = |v: &str| {
+        v.parse::<f64>()
+            .map_err(|_| format!("invalid coefficient '{}'", s))
+    };
+    match s.split_once(',') {
+        Some((re, im)) => Ok([parse(re)?, parse(im)?]),
+        None => Ok([parse(s)?, 0.0]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fractal::{FractalMath, MemType};
+    use crate::mem::Mem;
+    use crate::polynomial::{Polynomial, PolynomialFractal, RationalFractal};
+
+    #[test]
+    fn test_evaluate() {
+        // 1 + 2z + 3z^2
+        let p = Polynomial::new(vec![[1.0, 0.0], [2.0, 0.0], [3.0, 0.0]]);
+        let r = p.evaluate(2.0, 0.0);
+        assert_eq!(r.re, 17.0);
+        assert_eq!(r.im, 0.0);
+
+        // i z^2
+        let p = Polynomial::new(vec![[0.0, 0.0], [0.0, 0.0], [0.0, 1.0]]);
+        let r = p.evaluate(1.0, 1.0);
+        assert_eq!(r.re, -2.0);
+        assert_eq!(r.im, 0.0);
+    }
+
+    #[test]
+    fn test_degree() {
+        assert_eq!(Polynomial::power(3).degree(), 3);
+        assert_eq!(Polynomial::new(vec![[1.0, 0.0], [0.0, 0.0]]).degree(), 0);
+
+        let f = PolynomialFractal::power(2).compose(PolynomialFractal::power(3));
+        assert_eq!(f.degree(), 6);
+    }
+
+    #[test]
+    fn test_math_mandelbrot() {
+        let f = PolynomialFractal::power(2);
+        let mut m = Mem::new(3.0, 2.0);
+        let mut e = Mem::new(3.0, 2.0);
+
+        f.math(&mut m, 0.5, 0.4);
+        e.square();
+        e.plus(0.5, 0.4);
+        assert_eq!(m.re, e.re);
+        assert_eq!(m.im, e.im);
+    }
+
+    #[test]
+    fn test_math_compose() {
+        // f(f(z)), f = z^2 + c
+        let f = PolynomialFractal::power(2).compose(PolynomialFractal::power(2));
+        let mut m = Mem::new(0.0, 0.0);
+
+        f.math(&mut m, 1.0, 1.0);
+        assert_eq!(m.re, 1.0);
+        assert_eq!(m.im, 3.0);
+    }
+
+    #[test]
+    fn test_from_str() {
+        let f: PolynomialFractal = "# z^2\n0 0 1\n\n0 0,0 0,1".parse().unwrap();
+        assert_eq!(f.steps.len(), 2);
+        assert_eq!(f.steps[1].coefficients[2], [0.0, 1.0]);
+        assert_eq!(f.degree(), 4);
+    }
+
+    #[test]
+    fn test_definition() {
+        let p = Polynomial::new(vec![[0.1, -0.25], [1.0 / 3.0, 0.0]]);
+        let f = PolynomialFractal::power(2).compose(PolynomialFractal::new(vec![p]));
+
+        let read: PolynomialFractal = f.definition().unwrap().parse().unwrap();
+        assert_eq!(read.steps.len(), 2);
+        assert_eq!(read.steps[0].coefficients, f.steps[0].coefficients);
+        assert_eq!(read.steps[1].coefficients, f.steps[1].coefficients);
+
+        let r: RationalFractal = "0 0 1 / 0.5,1e-20 1".parse().unwrap();
+        let read: RationalFractal = r.definition().unwrap().parse().unwrap();
+        assert_eq!(read.steps[0].0.coefficients, r.steps[0].0.coefficients);
+        assert_eq!(read.steps[0].1.coefficients, r.steps[0].1.coefficients);
+    }
+
+    #[test]
+    fn test_from_str_error() {
+        assert!("0 x 1".parse::<PolynomialFractal>().is_err());
+        assert!("# nothing".parse::<PolynomialFractal>().is_err());
+    }
+
+    #[test]
+    fn test_rational() {
+        // z := z^2 / z + c = z + c
+        let f: RationalFractal = "0 0 1 / 0 1".parse().unwrap();
+        let mut m = Mem::new(2.0, 1.0);
+
+        f.math(&mut m, 0.5, 0.5);
+        assert_eq!(m.re, 2.5);
+        assert_eq!(m.im, 1.5);
+        assert_eq!(f.degree(), 1);
+    }
+
+    #[test]
+    fn test_rational_without_denominator() {
+        let f: RationalFractal = "0 0 1".parse().unwrap();
+        let mut m = Mem::new(3.0, 2.0);
+
+        f.math(&mut m, 0.0, 0.0);
+        assert_eq!(m.re, 5.0);
+        assert_eq!(m.im, 12.0);
+        assert_eq!(f.degree(), 2);
+    }
+}
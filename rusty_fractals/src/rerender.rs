@@ -0,0 +1,348 @@
+use crate::application;
+use crate::config::{EulerImage, MandelbrotImage, NebulaImage};
+use crate::euler::EULER_CHANNELS;
+use crate::fractal::FractalCalculationType::{
+    DynamicSequenceNebula, StaticImageMandelbrot, StaticImageNebula, StaticSequenceMandelbrot,
+    StaticSpectralImageEuler,
+};
+use crate::fractal::{FractalConfig, FractalMath, MemType, Optimizer};
+use crate::metadata;
+use crate::metadata::Metadata;
+use crate::palettes::PaletteName;
+use crate::resolution_multiplier::ResolutionMultiplier;
+use crate::supersampling::Supersampling;
+use std::fs;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+/**
+ * What to change when rendering a saved image again, None keeps the saved value
+ */
+pub struct Rerender {
+    // height follows the aspect ratio of the saved image
+    pub width_x: Option<usize>,
+    pub palette: Option<PaletteName>,
+    pub palette_zero: Option<PaletteName>,
+    // sub-samples of Mandelbrot pixels
+    pub resolution_multiplier: Option<ResolutionMultiplier>,
+}
+
+pub const AS_SAVED: Rerender = Rerender {
+    width_x: None,
+    palette: None,
+    palette_zero: None,
+    resolution_multiplier: None,
+};
+
+/**
+ * Re-render mode of the fractal binaries, args are the command line arguments without the program
+ * false if the first argument isn't a saved image, the binary then renders its own scene
+ * cargo run -- mandelbrot_0.png width=3840 palette=LinearBlue palette_zero=LinearGold multiplier=Square3
+ * - the options are optional, see Rerender
+ * - fractal is made of the saved metadata, like formula text or initialized sequences
+ */
+pub fn execute_from_args<F, M>(
+    args: &[String],
+    fractal_name: &'static str,
+    oo: Option<Optimizer>,
+    fractal: impl FnOnce(&Metadata) -> Result<F, String>,
+) -> bool
+where
+    F: FractalMath<M> + 'static,
+    M: MemType<M> + 'static,
+{
+    let Some(path) = args.first().filter(|path| is_saved_image(path)) else {
+        return false;
+    };
+    let saved = parse_options(&args[1..]).and_then(|rerender| {
+        let m = read_metadata_of(path, fractal_name)?;
+        let fractal = fractal(&m).map_err(|e| format!("{}: {}", path, e))?;
+        Ok((config_from_metadata(&m, fractal_name, &rerender), fractal))
+    });
+    match saved {
+        Ok((config, fractal)) => application::execute_o(config, fractal, oo),
+        Err(e) => println!("{}", e),
+    }
+    true
+}
+
+fn is_saved_image(path: &str) -> bool {
+    let extension = Path::new(path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase())
+        .unwrap_or_default();
+    matches!(extension.as_str(), "png" | "jpg" | "jpeg" | "webp" | "json")
+}
+
+/**
+ * Options as name=value, width=3840 palette=LinearBlue palette_zero=LinearGold multiplier=Square3
+ */
+fn parse_options(options: &[String]) -> Result<Rerender, String> {
+    let mut rerender = AS_SAVED;
+    for option in options {
+        let (name, value) = option
+            .split_once('=')
+            .ok_or(format!("invalid option '{}', expected name=value", option))?;
+        match name {
+            "width" => {
+                rerender.width_x = Some(value.parse().map_err(|_| format!("invalid width '{}'", value))?)
+            }
+            "palette" => rerender.palette = Some(metadata::parse_palette(value)?),
+            "palette_zero" => rerender.palette_zero = Some(metadata::parse_palette(value)?),
+            "multiplier" => rerender.resolution_multiplier = Some(metadata::parse_resolution_multiplier(value)?),
+            _ => return Err(format!("unknown option '{}'", name)),
+        }
+    }
+    Ok(rerender)
+}
+
+/**
+ * Metadata of saved image
+ * - PNG text chunks
+ * - JSON sidecar next to JPG and WebP, or the sidecar itself
+ */
+pub fn read_metadata(path: &str) -> Result<Metadata, String> {
+    let path = Path::new(path);
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase())
+        .unwrap_or_default();
+    if extension == "png" {
+        let file = File::open(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let reader = png::Decoder::new(BufReader::new(file))
+            .read_info()
+            .map_err(|e| format!("{}: {}", path.display(), e))?;
+        let entries: Vec<(String, String)> = reader
+            .info()
+            .uncompressed_latin1_text
+            .iter()
+            .map(|t| (t.keyword.clone(), t.text.clone()))
+            .collect();
+        return Metadata::from_entries(&entries).map_err(|e| format!("{}: {}", path.display(), e));
+    }
+    let sidecar = path.with_extension("json");
+    let json = fs::read_to_string(&sidecar).map_err(|e| format!("{}: {}", sidecar.display(), e))?;
+    Metadata::from_json(&json).map_err(|e| format!("{}: {}", sidecar.display(), e))
+}
+
+/**
+ * Config to render the saved image again, by the fractal of the same name
+ * Zoom video frames are rendered as static images, Euler uses the saved spectral channels.
+ * Coloring settings are the saved ones.
+ */
+pub fn config_from_image(
+    path: &str,
+    fractal_name: &'static str,
+    rerender: &Rerender,
+) -> Result<FractalConfig, String> {
+    let metadata = read_metadata_of(path, fractal_name)?;
+    Ok(config_from_metadata(&metadata, fractal_name, rerender))
+}
+
+fn read_metadata_of(path: &str, fractal_name: &str) -> Result<Metadata, String> {
+    let metadata = read_metadata(path)?;
+    if metadata.name != fractal_name {
+        return Err(format!("{}: rendered by {}, not by {}", path, metadata.name, fractal_name));
+    }
+    Ok(metadata)
+}
+
+/**
+ * Config of the saved image for the fractal of the given name
+ * Mandelbrot pixels aren't multiplied, a new resolution multiplier supersamples them instead.
+ */
+pub fn config_from_metadata(m: &Metadata, name: &'static str, rerender: &Rerender) -> FractalConfig {
+    let width_x = rerender.width_x.unwrap_or(m.width_x);
+    let height_y = (m.height_y * width_x / m.width_x).max(1);
+    let resolution_multiplier = rerender.resolution_multiplier.unwrap_or(m.resolution_multiplier);
+    let palette = rerender.palette.clone().unwrap_or_else(|| m.palette.clone());
+    let palette_zero = rerender.palette_zero.clone().unwrap_or_else(|| m.palette_zero.clone());
+    let supersampling = match (m.fractal_calc_type, rerender.resolution_multiplier) {
+        (StaticImageMandelbrot | StaticSequenceMandelbrot, Some(rm)) => match m.supersampling {
+            Supersampling::Jittered(_) => Supersampling::Jittered(rm),
+            Supersampling::Adaptive(_, difference) => Supersampling::Adaptive(rm, difference),
+            Supersampling::Nothing | Supersampling::Grid(_) => Supersampling::Grid(rm),
+        },
+        _ => m.supersampling,
+    };
+    let config = match m.fractal_calc_type {
+        StaticImageNebula | DynamicSequenceNebula => NebulaImage {
+            name,
+            orbits: m.orbits,
+            iteration_min: m.iteration_min,
+            iteration_max: m.iteration_max,
+            resolution_multiplier,
+            palette,
+            width_x,
+            height_y,
+            width_re: m.width_re,
+            center_re: m.center_re,
+            center_im: m.center_im,
+        }
+        .init(),
+        StaticImageMandelbrot | StaticSequenceMandelbrot => MandelbrotImage {
+            name,
+            iteration_max: m.iteration_max,
+            palette,
+            palette_zero,
+            width_x,
+            height_y,
+            width_re: m.width_re,
+            center_re: m.center_re,
+            center_im: m.center_im,
+        }
+        .init(),
+        StaticSpectralImageEuler => EulerImage {
+            name,
+            orbits: m.orbits,
+            iteration_min: m.iteration_min,
+            iteration_max: m.iteration_max,
+            resolution_multiplier,
+            width_x,
+            height_y,
+            width_re: m.width_re,
+            center_re: m.center_re,
+            center_im: m.center_im,
+            spectral_channels: EULER_CHANNELS,
+        }
+        .init()
+        .spectral_channels(m.spectral_channels.clone()),
+    };
+    config
+        .palette_space(m.palette_space)
+        .palette_shift(m.palette_shift)
+        .coloring(m.coloring_mode)
+        .orbit_trap(m.orbit_trap, m.orbit_trap_coloring)
+        .supersampling(supersampling)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::coloring_mode::ColoringMode::Log;
+    use crate::fractal::FractalCalculationType::StaticImageMandelbrot;
+    use crate::files::OutputFormat::{Jpg, Png};
+    use crate::files::{save_image, OutputFiles};
+    use crate::fractal::init_trivial_dynamic_config;
+    use crate::fractal::FractalCalculationType::{DynamicSequenceNebula, StaticImageNebula};
+    use crate::metadata::init_trivial;
+    use crate::palettes::PaletteName::{File, LinearBlue};
+    use crate::fractal::TrivialFractal;
+    use crate::rerender::{
+        config_from_image, config_from_metadata, execute_from_args, parse_options, read_metadata, Rerender, AS_SAVED,
+    };
+    use crate::resolution_multiplier::ResolutionMultiplier::{Square3, Square5};
+    use crate::supersampling::Supersampling::{Adaptive, Grid, Nothing};
+    use crate::{area, data_image};
+
+    #[test]
+    fn test_read_metadata() {
+        let c = init_trivial_dynamic_config(5);
+        let di = data_image::init(&c, &area::init(&c));
+        let mut m = init_trivial("Rerender Read", 2);
+        m.center_re = -0.743643887037151;
+        m.palette = File("palettes/my \"warm\".gpl".to_string());
+
+        for format in [Png, Jpg(90)] {
+            let files = OutputFiles { dir: "test_output_rerender", template: "{name}_{index}", format };
+            save_image(&di, &files, &m).unwrap();
+        }
+        let from_png = read_metadata("test_output_rerender/rerender_read_2.png").unwrap();
+        let from_jpg = read_metadata("test_output_rerender/rerender_read_2.jpg").unwrap();
+        std::fs::remove_dir_all("test_output_rerender").unwrap();
+
+        for read in [from_png, from_jpg] {
+            assert_eq!(read.entries(), m.entries());
+        }
+    }
+
+    #[test]
+    fn test_config_from_image() {
+        let c = init_trivial_dynamic_config(5);
+        let di = data_image::init(&c, &area::init(&c));
+        let files = OutputFiles { dir: "test_output_rerender_config", template: "{name}", format: Png };
+        save_image(&di, &files, &init_trivial("Mandelbrot", 0)).unwrap();
+        let path = "test_output_rerender_config/mandelbrot.png";
+
+        let config = config_from_image(path, "Mandelbrot", &AS_SAVED);
+        let other = config_from_image(path, "Nebula", &AS_SAVED);
+        std::fs::remove_dir_all("test_output_rerender_config").unwrap();
+
+        let config = config.unwrap();
+        assert_eq!(config.name, "Mandelbrot");
+        assert_eq!(config.width_xl, 2);
+        assert_eq!(config.iteration_max, 5);
+        assert!(other.is_err());
+    }
+
+    #[test]
+    fn test_config_from_metadata() {
+        let mut m = init_trivial("Nebula", 40);
+        m.fractal_calc_type = DynamicSequenceNebula;
+        m.width_x = 1280;
+        m.height_y = 720;
+        m.width_re = 0.001;
+        m.coloring_mode = Log;
+        m.palette_shift = 0.25;
+        let rerender = Rerender {
+            width_x: Some(3840),
+            palette: Some(LinearBlue),
+            palette_zero: None,
+            resolution_multiplier: Some(Square3),
+        };
+
+        let config = config_from_metadata(&m, "Nebula", &rerender);
+
+        // zoom video frame becomes static image
+        assert!(config.fractal_calc_type == StaticImageNebula);
+        assert_eq!(config.width_xl, 3840);
+        assert_eq!(config.height_yl, 2160);
+        assert_eq!(config.width_re, 0.001);
+        assert!(config.resolution_multiplier == Square3);
+        assert!(matches!(config.palette, LinearBlue));
+        assert_eq!(config.coloring_mode, Log);
+        assert_eq!(config.palette_shift, 0.25);
+    }
+
+    #[test]
+    fn test_config_from_metadata_mandelbrot() {
+        let mut m = init_trivial("Mandelbrot", 0);
+        m.fractal_calc_type = StaticImageMandelbrot;
+        let rerender = Rerender { resolution_multiplier: Some(Square3), ..AS_SAVED };
+
+        assert!(config_from_metadata(&m, "Mandelbrot", &AS_SAVED).supersampling == Nothing);
+        assert!(config_from_metadata(&m, "Mandelbrot", &rerender).supersampling == Grid(Square3));
+        m.supersampling = Adaptive(Square5, 4);
+        assert!(config_from_metadata(&m, "Mandelbrot", &rerender).supersampling == Adaptive(Square3, 4));
+    }
+
+    #[test]
+    fn test_parse_options() {
+        let options: Vec<String> = ["width=3840", "palette=LinearBlue", "multiplier=Square3"]
+            .iter()
+            .map(|o| o.to_string())
+            .collect();
+
+        let rerender = parse_options(&options).unwrap();
+
+        assert_eq!(rerender.width_x, Some(3840));
+        assert!(matches!(rerender.palette, Some(LinearBlue)));
+        assert!(rerender.palette_zero.is_none());
+        assert!(rerender.resolution_multiplier == Some(Square3));
+        assert!(parse_options(&["width".to_string()]).is_err());
+        assert!(parse_options(&["height=10".to_string()]).is_err());
+        assert!(parse_options(&["multiplier=Square4".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_execute_from_args_no_image() {
+        let fractal = |_: &_| Ok(TrivialFractal);
+
+        assert!(!execute_from_args(&[], "Nebula", None, fractal));
+        assert!(!execute_from_args(&["formula.txt".to_string()], "Nebula", None, fractal));
+        // saved image which can't be read is handled by printing the error
+        assert!(execute_from_args(&["missing.png".to_string()], "Nebula", None, fractal));
+    }
+}
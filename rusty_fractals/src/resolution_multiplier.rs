@@ -1,4 +1,4 @@
-#[derive(PartialOrd, Ord, PartialEq, Eq, Clone, Copy)]
+#[derive(PartialOrd, Ord, PartialEq, Eq, Clone, Copy, Debug)]
 pub enum ResolutionMultiplier {
     // Single point at the center of Mandelbrot pixel
     Single,
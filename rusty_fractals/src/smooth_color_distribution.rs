@@ -0,0 +1,151 @@
+// The method used for smooth coloring is
+// - For each escaped pixel calculate continuous (normalized) iteration count
+//   n + 1 - ln(ln|z|) / ln(d), where d is the degree of the map
+// - Normalize the continuous value by its minimum and maximum on screen
+//   colors of zoom frames shift when that range changes
+// - Map the normalized value directly to position in spectrum, no ordering
+//   so that there are no bands of equal value pixels as by perfect coloring
+// - Interpolate color between the two nearest spectrum colors
+// - Mandelbrot set insides color by zero palette, the same way, by quad
+
+use crate::data_image::DataImage;
+use crate::palette::Palette;
+
+// for Mandelbrot like fractals
+struct Smooth {
+    x: usize,
+    y: usize,
+    smooth: f64,
+}
+
+/**
+ * Normalized iteration count of escaped calculation
+ * - iterator: iteration at which the calculation escaped
+ * - quad: |z|^2 at escape
+ * - degree: degree of the map, 2 for z^2 + c
+ *   maps of degree 0 or 1 don't escape by powers of z, degree 2 is used for them
+ */
+pub fn smooth_iteration(iterator: u64, quad: f64, degree: f64) -> f64 {
+    let degree = if degree > 1.0 { degree } else { 2.0 };
+    // ln|z| = ln(|z|^2) / 2
+    let log_z = quad.ln() / 2.0;
+    iterator as f64 + 1.0 - log_z.ln() / degree.ln()
+}
+
+pub fn smoothly_color_mandelbrot_values(data: &DataImage, palette: &Palette, palette_zero: &Palette) {
+    println!("smoothly_color_mandelbrot_values()");
+
+    let mut pixels: Vec<Smooth> = Vec::new();
+    let mut pixels_zero: Vec<Smooth> = Vec::new();
+
+    for y in 0..data.height_yp {
+        for x in 0..data.width_xp {
+            let (value, quad, smooth) = data.value_quad_smooth_at(x, y);
+            if value == 0 {
+                // inverted order, same as perfect coloring
+                pixels_zero.push(Smooth { x, y, smooth: -quad });
+            } else {
+                // smooth value isn't available for the pixel which escaped immediately
+                let smooth = smooth.unwrap_or(value as f64);
+                pixels.push(Smooth { x, y, smooth });
+            }
+        }
+    }
+
+    color_smooth(data, pixels, palette);
+    // Paint insides of Mandelbrot set
+    color_smooth(data, pixels_zero, palette_zero);
+}
+
+fn color_smooth(data: &DataImage, pixels: Vec<Smooth>, palette: &Palette) {
+    if palette.spectrum.is_empty() || pixels.is_empty() {
+        return;
+    }
+
+    let min = pixels.iter().map(|p| p.smooth).fold(f64::INFINITY, f64::min);
+    let max = pixels.iter().map(|p| p.smooth).fold(f64::NEG_INFINITY, f64::max);
+    let range = if max > min { max - min } else { 1.0 };
+
+    for sp in pixels {
+        data.color(sp.x, sp.y, palette.spectrum_value_at_fraction((sp.smooth - min) / range));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::area;
+    use crate::data_image::init;
+    use crate::fractal::init_trivial_dynamic_config;
+    use crate::palette::init_trivial;
+    use crate::pixel_states::DomainElementState::FinishedSuccess;
+    use crate::smooth_color_distribution::{smooth_iteration, smoothly_color_mandelbrot_values};
+    use image::Rgb;
+
+    #[test]
+    fn test_smooth_iteration() {
+        // |z| = 2, ln(ln 2) < 0
+        assert!(smooth_iteration(3, 4.0, 2.0) > 4.0);
+        // |z| = e
+        assert_eq!(smooth_iteration(3, 1.0f64.exp().powi(2), 2.0), 4.0);
+        // linear and constant maps
+        assert_eq!(smooth_iteration(3, 16.0, 1.0), smooth_iteration(3, 16.0, 2.0));
+        assert!(smooth_iteration(3, 16.0, 0.0).is_finite());
+    }
+
+    #[test]
+    fn test_smooth_iteration_continuous() {
+        // one more iteration of z^2 squares |z|, the value doesn't change
+        let a = smooth_iteration(3, 16.0, 2.0);
+        let b = smooth_iteration(4, 256.0, 2.0);
+        assert!((a - b).abs() < 1e-12);
+
+        let a = smooth_iteration(3, 16.0, 3.0);
+        let b = smooth_iteration(4, 4096.0, 3.0);
+        assert!((a - b).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_smoothly_color_mandelbrot_values() {
+        let c = init_trivial_dynamic_config(3);
+        let a = area::init(&c);
+        let di = init(&c, &a);
+        let p = init_trivial();
+
+        // 9 pixels, the highest values at the end of spectrum
+        for y in 0..3 {
+            for x in 0..3 {
+                di.set_pixel_mandelbrot(x, y, 5, 4.0, FinishedSuccess, 10);
+                di.set_pixel_smooth(x, y, (x + 3 * y) as f64);
+            }
+        }
+        smoothly_color_mandelbrot_values(&di, &p, &p);
+
+        assert_eq!(di.color_at(0, 0), Some(Rgb([255, 0, 0])));
+        assert_eq!(di.color_at(1, 1), Some(Rgb([0, 255, 0])));
+        assert_eq!(di.color_at(2, 2), Some(Rgb([0, 0, 255])));
+        // between the first and the second color
+        assert_eq!(di.color_at(2, 0), Some(Rgb([128, 128, 0])));
+    }
+
+    #[test]
+    fn test_smoothly_color_mandelbrot_values_not_ranked() {
+        let c = init_trivial_dynamic_config(3);
+        let di = init(&c, &area::init(&c));
+        let p = init_trivial();
+        for y in 0..3 {
+            for x in 0..3 {
+                di.set_pixel_mandelbrot(x, y, 5, 4.0, FinishedSuccess, 10);
+                di.set_pixel_smooth(x, y, 0.0);
+            }
+        }
+        di.set_pixel_smooth(1, 0, 1.0);
+        di.set_pixel_smooth(2, 2, 100.0);
+
+        smoothly_color_mandelbrot_values(&di, &p, &p);
+
+        // value close to minimum stays close to the first color, whatever its order
+        assert_eq!(di.color_at(0, 0), Some(Rgb([255, 0, 0])));
+        assert_eq!(di.color_at(1, 0), Some(Rgb([250, 5, 0])));
+        assert_eq!(di.color_at(2, 2), Some(Rgb([0, 0, 255])));
+    }
+}
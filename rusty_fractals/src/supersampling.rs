@@ -0,0 +1,190 @@
+use crate::data_image::{resolve_multiplier, DataImage};
+use crate::data_px::PxValues;
+use crate::resolution_multiplier::ResolutionMultiplier;
+use crate::resolution_multiplier::ResolutionMultiplier::{Single, Square2};
+use image::Rgb;
+
+/**
+ * Anti-aliasing of Mandelbrot images
+ * Color of each pixel is the average of colors of the pixel center and of its sub-samples.
+ * Sub-samples are colored by the same coloring mode and orbit trap as the pixels.
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Supersampling {
+    Nothing,
+    // sub-samples on regular grid, DataImage::wrap positions
+    Grid(ResolutionMultiplier),
+    // grid positions moved randomly within their sub-cells
+    Jittered(ResolutionMultiplier),
+    // sub-samples on regular grid, only for pixels at edges
+    // where some neighbor differs at least by the iteration difference, or by state
+    Adaptive(ResolutionMultiplier, u64),
+}
+
+impl Supersampling {
+    pub fn is_on(&self) -> bool {
+        !matches!(
+            self,
+            Supersampling::Nothing
+                | Supersampling::Grid(Single)
+                | Supersampling::Jittered(Single)
+                | Supersampling::Adaptive(Single, _)
+        )
+    }
+}
+
+/**
+ * Sub-sample positions around the pixel origin, the origin itself is not included
+ */
+pub fn sample_points(
+    data: &DataImage,
+    origin_re: f64,
+    origin_im: f64,
+    supersampling: Supersampling,
+    plank: f64,
+) -> Vec<[f64; 2]> {
+    match supersampling {
+        Supersampling::Nothing => Vec::new(),
+        Supersampling::Grid(rm) | Supersampling::Adaptive(rm, _) => {
+            data.wrap(origin_re, origin_im, rm, plank)
+        }
+        Supersampling::Jittered(rm) => {
+            // half of the distance between grid points
+            let h = match rm {
+                Square2 => plank / 6.0,
+                _ => plank / (2.0 * resolve_multiplier(rm)),
+            };
+            data.wrap(origin_re, origin_im, rm, plank)
+                .into_iter()
+                .map(|[re, im]| [re + rand::random_range(-h..h), im + rand::random_range(-h..h)])
+                .collect()
+        }
+    }
+}
+
+/**
+ * Pixel needs sub-samples, if its neighbor is in another state, on the other side of the set border
+ * or if their values differ at least by min_difference
+ */
+pub fn needs_refinement(data: &DataImage, x: usize, y: usize, min_difference: u64) -> bool {
+    let (value, state) = data.value_state_at(x, y);
+    for ny in y.saturating_sub(1)..(y + 2).min(data.height_yp) {
+        for nx in x.saturating_sub(1)..(x + 2).min(data.width_xp) {
+            let (n_value, n_state) = data.value_state_at(nx, ny);
+            // value 0 is inside of the set
+            if n_state != state
+                || (n_value == 0) != (value == 0)
+                || n_value.abs_diff(value) >= min_difference
+            {
+                return true;
+            }
+        }
+    }
+    false
+}
+
+/**
+ * Calculated sub-samples of a static image, kept for color cycling
+ * - refined pixels by pixel index x + y * width_xp
+ * - values of one sub-sample of each refined pixel, for each layer
+ */
+#[derive(Default)]
+pub struct Supersamples {
+    pub refined: Vec<usize>,
+    pub layers: Vec<Vec<PxValues>>,
+}
+
+/**
+ * Sum of colors of the pixel and its sub-samples, the colors themselves are not kept
+ */
+#[derive(Clone, Copy, Default)]
+pub struct ColorSum {
+    sum: [u64; 3],
+    count: u64,
+}
+
+impl ColorSum {
+    pub fn add(&mut self, color: Rgb<u8>) {
+        for (s, v) in self.sum.iter_mut().zip(color.0) {
+            *s += v as u64;
+        }
+        self.count += 1;
+    }
+
+    pub fn average(&self) -> Option<Rgb<u8>> {
+        let n = self.count;
+        (n > 0).then(|| Rgb(self.sum.map(|s| ((s + n / 2) / n) as u8)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::area;
+    use crate::data_image::init;
+    use crate::fractal::init_trivial_dynamic_config;
+    use crate::pixel_states::DomainElementState::{FinishedSuccess, FinishedTooShort};
+    use crate::resolution_multiplier::ResolutionMultiplier::{Single, Square3};
+    use crate::supersampling::{needs_refinement, sample_points, ColorSum, Supersampling};
+    use image::Rgb;
+
+    #[test]
+    fn test_is_on() {
+        assert!(!Supersampling::Nothing.is_on());
+        assert!(!Supersampling::Grid(Single).is_on());
+        assert!(Supersampling::Grid(Square3).is_on());
+        assert!(!Supersampling::Jittered(Single).is_on());
+        assert!(Supersampling::Jittered(Square3).is_on());
+        assert!(!Supersampling::Adaptive(Single, 5).is_on());
+        assert!(Supersampling::Adaptive(Square3, 5).is_on());
+    }
+
+    #[test]
+    fn test_needs_refinement() {
+        let c = init_trivial_dynamic_config(5);
+        let di = init(&c, &area::init(&c));
+        for y in 0..5 {
+            for x in 0..5 {
+                di.set_pixel_mandelbrot(x, y, 5 + x as u64, 1.0, FinishedSuccess, 100);
+            }
+        }
+        di.set_pixel_mandelbrot(4, 0, 90, 1.0, FinishedSuccess, 100);
+        di.set_pixel_mandelbrot(0, 4, 6, 1.0, FinishedTooShort, 100);
+
+        // flat region
+        assert!(!needs_refinement(&di, 1, 1, 5));
+        // value difference
+        assert!(needs_refinement(&di, 3, 1, 5));
+        assert!(!needs_refinement(&di, 3, 1, 100));
+        // state difference
+        assert!(needs_refinement(&di, 1, 3, 100));
+        di.set_pixel_mandelbrot(2, 2, 100, 1.0, FinishedSuccess, 100);
+        // inside of the set
+        assert!(needs_refinement(&di, 1, 1, 100));
+    }
+
+    #[test]
+    fn test_sample_points() {
+        let c = init_trivial_dynamic_config(3);
+        let di = init(&c, &area::init(&c));
+
+        let grid = sample_points(&di, 0.0, 0.0, Supersampling::Grid(Square3), 0.3);
+        let jittered = sample_points(&di, 0.0, 0.0, Supersampling::Jittered(Square3), 0.3);
+
+        assert_eq!(grid.len(), 8);
+        assert_eq!(jittered.len(), 8);
+        for ([gre, gim], [jre, jim]) in grid.iter().zip(&jittered) {
+            assert!((gre - jre).abs() <= 0.05 && (gim - jim).abs() <= 0.05);
+        }
+        assert!(sample_points(&di, 0.0, 0.0, Supersampling::Nothing, 0.3).is_empty());
+    }
+
+    #[test]
+    fn test_color_sum() {
+        let mut sum = ColorSum::default();
+        assert_eq!(sum.average(), None);
+
+        sum.add(Rgb([0, 10, 255]));
+        sum.add(Rgb([255, 11, 255]));
+        assert_eq!(sum.average(), Some(Rgb([128, 11, 255])));
+    }
+}
@@ -0,0 +1,285 @@
+// The method used for temporally stable coloring of zoom videos is
+// - Gather all screen pixels and order them by value, same as perfect coloring
+// - Find the values at which each palette color begins, the quantile boundaries
+// - Blend these boundaries with the boundaries of previous frame
+//   boundary := previous + alpha * (current - previous), alpha = 1 - e^(-1 / time_constant)
+// - Color each pixel by the blended boundaries
+// The value distribution may change fast between frames, the colors change slowly
+
+use crate::constants::COLORING_THRESHOLD;
+use crate::data_image::DataImage;
+use crate::palette::Palette;
+
+pub struct TemporalColoring {
+    // number of frames for the boundaries to move by 1 - 1/e of the change
+    pub time_constant: f64,
+    // boundaries of previous frame
+    boundaries: Option<Vec<f64>>,
+    boundaries_zero: Option<Vec<f64>>,
+}
+
+pub fn init(time_constant: f64) -> TemporalColoring {
+    TemporalColoring {
+        time_constant,
+        boundaries: None,
+        boundaries_zero: None,
+    }
+}
+
+impl TemporalColoring {
+    pub fn is_on(&self) -> bool {
+        self.time_constant > 0.0
+    }
+
+    pub fn color_nebula_values(&mut self, data: &DataImage, palette: &Palette) {
+        println!("temporally_color_nebula_values()");
+
+        let mut keys: Vec<f64> = Vec::new();
+        for y in 0..data.height_yp {
+            for x in 0..data.width_xp {
+                let v = data.value_at(x, y);
+                if v > COLORING_THRESHOLD {
+                    keys.push(v as f64);
+                }
+            }
+        }
+        let boundaries = self.blend(self.boundaries.as_deref(), keys, palette);
+
+        for y in 0..data.height_yp {
+            for x in 0..data.width_xp {
+                let v = data.value_at(x, y);
+                let color_index = if v <= COLORING_THRESHOLD {
+                    // color zero-value elements and low-value-noise with the darkest color
+                    0
+                } else {
+                    color_index(&boundaries, v as f64)
+                };
+                data.color(x, y, palette.spectrum_value(color_index));
+            }
+        }
+        self.boundaries = Some(boundaries);
+    }
+
+    pub fn color_mandelbrot_values(
+        &mut self,
+        data: &DataImage,
+        palette: &Palette,
+        palette_zero: &Palette,
+    ) {
+        println!("temporally_color_mandelbrot_values()");
+
+        let mut keys: Vec<f64> = Vec::new();
+        let mut keys_zero: Vec<f64> = Vec::new();
+        for y in 0..data.height_yp {
+            for x in 0..data.width_xp {
+                let (value, _, quad, _) = data.values_state_quad_color_at(x, y);
+                if value == 0 {
+                    keys_zero.push(mandelbrot_zero_key(quad));
+                } else {
+                    keys.push(mandelbrot_key(value, quad));
+                }
+            }
+        }
+        let boundaries = self.blend(self.boundaries.as_deref(), keys, palette);
+        let boundaries_zero = self.blend(self.boundaries_zero.as_deref(), keys_zero, palette_zero);
+
+        paint_mandelbrot_values(data, palette, palette_zero, &boundaries, &boundaries_zero);
+        self.boundaries = Some(boundaries);
+        self.boundaries_zero = Some(boundaries_zero);
+    }
+
+    /**
+     * Color by boundaries of the last frame, the boundaries don't move
+     * for sub-samples of the frame, nothing is colored before the first frame
+     */
+    pub fn recolor_mandelbrot_values(&self, data: &DataImage, palette: &Palette, palette_zero: &Palette) {
+        if let (Some(boundaries), Some(boundaries_zero)) = (&self.boundaries, &self.boundaries_zero) {
+            paint_mandelbrot_values(data, palette, palette_zero, boundaries, boundaries_zero);
+        }
+    }
+
+    /**
+     * Boundaries of current frame blended with the previous ones
+     */
+    fn blend(&self, previous: Option<&[f64]>, mut keys: Vec<f64>, palette: &Palette) -> Vec<f64> {
+        keys.sort_by(|first, second| first.total_cmp(second));
+        let current = quantile_boundaries(&keys, palette.spectrum.len());
+        match previous {
+            Some(previous) if previous.len() == current.len() => {
+                let alpha = 1.0 - (-1.0 / self.time_constant).exp();
+                previous
+                    .iter()
+                    .zip(current.iter())
+                    .map(|(p, c)| p + alpha * (c - p))
+                    .collect()
+            }
+            _ => current,
+        }
+    }
+}
+
+/**
+ * Order by value, the same value order by quad, inverted
+ */
+fn mandelbrot_key(value: u64, quad: f64) -> f64 {
+    value as f64 + 1.0 / (1.0 + quad)
+}
+
+fn mandelbrot_zero_key(quad: f64) -> f64 {
+    -quad
+}
+
+fn paint_mandelbrot_values(
+    data: &DataImage,
+    palette: &Palette,
+    palette_zero: &Palette,
+    boundaries: &[f64],
+    boundaries_zero: &[f64],
+) {
+    for y in 0..data.height_yp {
+        for x in 0..data.width_xp {
+            let (value, _, quad, _) = data.values_state_quad_color_at(x, y);
+            if value == 0 {
+                // Paint insides of Mandelbrot set
+                if !palette_zero.spectrum.is_empty() {
+                    let ci = color_index(boundaries_zero, mandelbrot_zero_key(quad));
+                    data.color(x, y, palette_zero.spectrum_value(ci));
+                }
+            } else {
+                let ci = color_index(boundaries, mandelbrot_key(value, quad));
+                data.color(x, y, palette.spectrum_value(ci));
+            }
+        }
+    }
+}
+
+/**
+ * Values at which each palette color, except the first one, begins
+ * the same amount of pixels for each color, mismatched pixels colored by the first one
+ */
+fn quantile_boundaries(sorted: &[f64], palette_color_count: usize) -> Vec<f64> {
+    if palette_color_count == 0 {
+        return Vec::new();
+    }
+    let single_color_use = sorted.len() / palette_color_count;
+    let left = sorted.len() - palette_color_count * single_color_use;
+    (1..palette_color_count)
+        .map(|ci| match sorted.get(left + ci * single_color_use) {
+            Some(v) => *v,
+            // not enough pixels, don't use higher colors
+            None => f64::MAX,
+        })
+        .collect()
+}
+
+fn color_index(boundaries: &[f64], key: f64) -> usize {
+    boundaries.partition_point(|b| *b <= key)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::area;
+    use crate::data_image;
+    use crate::fractal::init_trivial_dynamic_config;
+    use crate::palette::init_trivial;
+    use crate::pixel_states::DomainElementState::FinishedSuccess;
+    use crate::temporal_color_distribution::{color_index, init, mandelbrot_key, quantile_boundaries};
+    use image::Rgb;
+
+    #[test]
+    fn test_quantile_boundaries() {
+        let sorted = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0];
+        // 2 pixels per color, 1 left for the first color
+        assert_eq!(quantile_boundaries(&sorted, 3), vec![4.0, 6.0]);
+        assert_eq!(quantile_boundaries(&[1.0], 3), vec![f64::MAX, f64::MAX]);
+        assert!(quantile_boundaries(&sorted, 0).is_empty());
+    }
+
+    #[test]
+    fn test_color_index() {
+        let boundaries = [4.0, 6.0];
+        assert_eq!(color_index(&boundaries, 3.0), 0);
+        assert_eq!(color_index(&boundaries, 4.0), 1);
+        assert_eq!(color_index(&boundaries, 9.0), 2);
+    }
+
+    #[test]
+    fn test_mandelbrot_key() {
+        assert!(mandelbrot_key(5, 4.0) < mandelbrot_key(6, 100.0));
+        assert!(mandelbrot_key(5, 9.0) < mandelbrot_key(5, 4.0));
+    }
+
+    #[test]
+    fn test_blend() {
+        let p = init_trivial();
+        let mut tc = init(1.0);
+
+        // the first frame uses its own boundaries
+        let b = tc.blend(None, vec![1.0, 2.0, 3.0], &p);
+        assert_eq!(b, vec![2.0, 3.0]);
+
+        // next frame moves by 1 - 1/e of the change
+        let b = tc.blend(Some(&b), vec![11.0, 12.0, 13.0], &p);
+        let alpha = 1.0 - (-1.0f64).exp();
+        assert!((b[0] - (2.0 + alpha * 10.0)).abs() < 1e-12);
+
+        tc.time_constant = 1e-9;
+        let b = tc.blend(Some(&b), vec![11.0, 12.0, 13.0], &p);
+        assert_eq!(b, vec![12.0, 13.0]);
+    }
+
+    #[test]
+    fn test_color_mandelbrot_values() {
+        let c = init_trivial_dynamic_config(3);
+        let a = area::init(&c);
+        let di = data_image::init(&c, &a);
+        let p = init_trivial();
+        let mut tc = init(2.0);
+
+        for y in 0..3 {
+            for x in 0..3 {
+                di.set_pixel_mandelbrot(x, y, (1 + x + 3 * y) as u64, 5.0, FinishedSuccess, 100);
+            }
+        }
+        tc.color_mandelbrot_values(&di, &p, &p);
+        assert_eq!(di.color_at(0, 0), Some(Rgb([255, 0, 0])));
+        assert_eq!(di.color_at(2, 2), Some(Rgb([0, 0, 255])));
+
+        // all values grow, the mapping follows slowly
+        // the lowest value isn't the first color anymore
+        for y in 0..3 {
+            for x in 0..3 {
+                di.set_pixel_mandelbrot(x, y, (10 + x + 3 * y) as u64, 5.0, FinishedSuccess, 100);
+            }
+        }
+        tc.color_mandelbrot_values(&di, &p, &p);
+        assert_eq!(di.color_at(0, 0), Some(Rgb([0, 255, 0])));
+        assert_eq!(di.color_at(2, 2), Some(Rgb([0, 0, 255])));
+    }
+
+    #[test]
+    fn test_recolor_mandelbrot_values() {
+        let c = init_trivial_dynamic_config(3);
+        let di = data_image::init(&c, &area::init(&c));
+        let p = init_trivial();
+        let mut tc = init(2.0);
+        for y in 0..3 {
+            for x in 0..3 {
+                di.set_pixel_mandelbrot(x, y, (1 + x + 3 * y) as u64, 5.0, FinishedSuccess, 100);
+            }
+        }
+
+        // nothing to follow yet
+        tc.recolor_mandelbrot_values(&di, &p, &p);
+        assert_eq!(di.color_at(0, 0), None);
+
+        tc.color_mandelbrot_values(&di, &p, &p);
+        di.set_pixel_mandelbrot(2, 2, 1, 5.0, FinishedSuccess, 100);
+        tc.recolor_mandelbrot_values(&di, &p, &p);
+        tc.recolor_mandelbrot_values(&di, &p, &p);
+
+        // the same boundaries, the highest pixel moved to the first color
+        assert_eq!(di.color_at(2, 2), Some(Rgb([255, 0, 0])));
+        assert_eq!(di.color_at(1, 2), Some(Rgb([0, 0, 255])));
+    }
+}
@@ -0,0 +1,612 @@
+use crate::area;
+use crate::area::Area;
+use crate::constants::{COLORING_THRESHOLD, NEIGHBOURS};
+use crate::files::OutputFiles;
+use crate::fractal::FractalCalculationType::StaticImageNebula;
+use crate::fractal::{FractalConfig, FractalMath, MemType};
+use crate::machine;
+use crate::machine::Machine;
+use crate::metadata::Metadata;
+use crate::palette::Palette;
+use crate::pixel_states::DomainElementState::FinishedSuccess;
+use crate::resolution_multiplier::ResolutionMultiplier::Single;
+use rayon::prelude::*;
+use std::collections::HashMap;
+use std::fs;
+use std::fs::{File, OpenOptions};
+use std::io::{BufReader, BufWriter, ErrorKind, Read, Write};
+use std::sync::Mutex;
+
+// pixels whose origins are generated and calculated by one thread,
+// before their path elements are handed over to spill buffers
+const ORIGINS_CHUNK: usize = 1024;
+// path elements of one tile kept in memory, before they are appended to the spill file of the tile
+const SPILL_BUFFER: usize = 1 << 16;
+
+/**
+ * Nebula images larger than memory
+ * Each calculation path of the whole image domain is calculated once, so are the wrapped origins
+ * of resolution multiplier. Path elements are mapped to pixels of the whole image area and spilled
+ * to files of their tiles. Tiles are then accumulated one by one from their spill files,
+ * values of each tile are streamed to disk, the final PNG is stitched from them with perfect coloring.
+ * Disk space of spill files is 4 bytes for each recorded path element.
+ * Tiles don't have Areas of their own, overlapping tile Areas would calculate each path again
+ * for every tile it crosses, and path length would be tested within the tile instead of the image.
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Tiling {
+    Nothing,
+    // tile side in pixels
+    Tiles(usize),
+}
+
+impl Tiling {
+    pub fn is_on(&self) -> bool {
+        matches!(self, Tiling::Tiles(_))
+    }
+}
+
+/**
+ * Pixels [from, to) of the whole image
+ */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Tile {
+    pub column: usize,
+    pub row: usize,
+    pub x: (usize, usize),
+    pub y: (usize, usize),
+}
+
+impl Tile {
+    pub fn width(&self) -> usize {
+        self.x.1 - self.x.0
+    }
+
+    pub fn height(&self) -> usize {
+        self.y.1 - self.y.0
+    }
+}
+
+/**
+ * Tiles by rows, from the top left
+ */
+pub fn layout(width_xl: usize, height_yl: usize, side: usize) -> Vec<Tile> {
+    let side = side.max(1);
+    let mut tiles = Vec::new();
+    for (row, y) in (0..height_yl).step_by(side).enumerate() {
+        for (column, x) in (0..width_xl).step_by(side).enumerate() {
+            tiles.push(Tile {
+                column,
+                row,
+                x: (x, (x + side).min(width_xl)),
+                y: (y, (y + side).min(height_yl)),
+            });
+        }
+    }
+    tiles
+}
+
+/**
+ * Config of the machine which calculates the paths, of one pixel only
+ * The paths are calculated by the whole image area, the data image of the machine isn't used.
+ */
+fn path_config(config: &FractalConfig) -> FractalConfig {
+    let mut c = config.clone();
+    c.width_xl = 1;
+    c.width_xp = 2;
+    c.height_yl = 1;
+    c.height_yp = 2;
+    c
+}
+
+/**
+ * Origins of the whole image, which of them have good paths and which are finished successfully
+ * Two bytes per pixel, instead of the whole DataPx
+ */
+struct Domain {
+    width_xp: usize,
+    height_yp: usize,
+    numbers_re: Vec<f64>,
+    numbers_im: Vec<f64>,
+    recorded: Vec<bool>,
+    success: Vec<bool>,
+}
+
+fn calculate_domain<F, M>(machine: &Machine<F, M>, area: &Area) -> Domain
+where
+    F: FractalMath<M>,
+    M: MemType<M>,
+{
+    println!("calculate_domain()");
+    let width_xp = area.width_xp();
+    let height_yp = area.height_yp();
+    let numbers_re = area.screen_to_domain_re_copy();
+    let numbers_im = area.screen_to_domain_im_copy();
+    let (recorded, success) = (0..width_xp * height_yp)
+        .into_par_iter()
+        .map(|i| {
+            let (iterator, length) =
+                machine.path_length(numbers_re[i % width_xp], numbers_im[i / width_xp], area);
+            (
+                machine.path_test(length, iterator),
+                machine.state_from_path_length(iterator, length) == FinishedSuccess,
+            )
+        })
+        .unzip();
+    Domain { width_xp, height_yp, numbers_re, numbers_im, recorded, success }
+}
+
+impl Domain {
+    fn origin(&self, i: usize) -> (f64, f64) {
+        (self.numbers_re[i % self.width_xp], self.numbers_im[i / self.width_xp])
+    }
+
+    /**
+     * Same as DataImage::is_on_mandelbrot_horizon
+     */
+    fn is_on_mandelbrot_horizon(&self, i: usize) -> bool {
+        let (x, y) = (i % self.width_xp, i / self.width_xp);
+        let neigh = NEIGHBOURS as usize;
+        for yy in y.saturating_sub(neigh)..(y + neigh + 1).min(self.height_yp) {
+            for xx in x.saturating_sub(neigh)..(x + neigh + 1).min(self.width_xp) {
+                if self.success[xx + yy * self.width_xp] {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+}
+
+/**
+ * Path elements of each tile, as pixel index within the tile, u32 little endian
+ * Elements are buffered per tile and appended to the tile file when the buffer is full,
+ * so that only a few files are open at any time.
+ */
+struct Spill {
+    side: usize,
+    columns: usize,
+    tiles: Vec<Tile>,
+    paths: Vec<String>,
+    buffers: Vec<Mutex<Vec<u32>>>,
+}
+
+fn create_spill(tiles: &[Tile], side: usize, paths: Vec<String>) -> Result<Spill, String> {
+    for path in &paths {
+        File::create(path).map_err(|e| format!("{}: {}", path, e))?;
+    }
+    Ok(Spill {
+        side: side.max(1),
+        columns: tiles.iter().map(|t| t.column).max().map_or(0, |c| c + 1),
+        tiles: tiles.to_vec(),
+        paths,
+        buffers: tiles.iter().map(|_| Mutex::new(Vec::new())).collect(),
+    })
+}
+
+impl Spill {
+    /**
+     * Tile index and pixel index within the tile, of pixel x, y of the whole image
+     */
+    fn locate(&self, x: usize, y: usize) -> (usize, u32) {
+        let ti = (y / self.side) * self.columns + x / self.side;
+        let tile = &self.tiles[ti];
+        (ti, ((x - tile.x.0) + (y - tile.y.0) * tile.width()) as u32)
+    }
+
+    fn append(&self, ti: usize, elements: &[u32]) -> Result<(), String> {
+        let mut buffer = self.buffers[ti].lock().unwrap();
+        buffer.extend_from_slice(elements);
+        if buffer.len() >= SPILL_BUFFER {
+            append_to_file(&self.paths[ti], &buffer)?;
+            buffer.clear();
+        }
+        Ok(())
+    }
+
+    fn flush(&self) -> Result<(), String> {
+        for (buffer, path) in self.buffers.iter().zip(&self.paths) {
+            let mut buffer = buffer.lock().unwrap();
+            append_to_file(path, &buffer)?;
+            buffer.clear();
+        }
+        Ok(())
+    }
+}
+
+fn append_to_file(path: &str, elements: &[u32]) -> Result<(), String> {
+    let file = OpenOptions::new()
+        .append(true)
+        .open(path)
+        .map_err(|e| format!("{}: {}", path, e))?;
+    let mut writer = BufWriter::new(file);
+    for e in elements {
+        writer
+            .write_all(&e.to_le_bytes())
+            .map_err(|e| format!("{}: {}", path, e))?;
+    }
+    writer.flush().map_err(|e| format!("{}: {}", path, e))
+}
+
+/**
+ * Each path is calculated once, path length is tested within the whole image
+ * Path elements are mapped to pixels by the whole image area and spilled to their tiles.
+ * Origins of pixel i are generated only when its chunk of pixels is calculated, none are kept.
+ */
+fn calculate_paths<F, M, O, I>(machine: &Machine<F, M>, area: &Area, origins: O, spill: &Spill) -> Result<(), String>
+where
+    F: FractalMath<M>,
+    M: MemType<M>,
+    O: Fn(usize) -> I + Sync,
+    I: IntoIterator<Item = [f64; 2]>,
+{
+    let (width_xl, height_yl) = (area.width_xp() - 1, area.height_yp() - 1);
+    let pixels = area.width_xp() * area.height_yp();
+    (0..pixels.div_ceil(ORIGINS_CHUNK)).into_par_iter().try_for_each(|chunk| {
+        let mut elements: HashMap<usize, Vec<u32>> = HashMap::new();
+        let from = chunk * ORIGINS_CHUNK;
+        for [origin_re, origin_im] in (from..(from + ORIGINS_CHUNK).min(pixels)).flat_map(&origins) {
+            let (iterator, length) = machine.path_length(origin_re, origin_im, area);
+            if machine.path_test(length, iterator) {
+                let (path, _) = machine.path_elements(origin_re, origin_im, iterator, area);
+                machine.stats.paths_new_points_amount_add(path.len());
+                for [re, im] in path {
+                    let (x, y) = area.point_to_pixel(re, im);
+                    // the last pixel column and row aren't part of the image
+                    if x < width_xl && y < height_yl {
+                        let (ti, pi) = spill.locate(x, y);
+                        elements.entry(ti).or_default().push(pi);
+                    }
+                }
+            }
+        }
+        elements.iter().try_for_each(|(ti, e)| spill.append(*ti, e))
+    })
+}
+
+/**
+ * Origins of the whole image with good paths, and the wrapped origins of resolution multiplier
+ */
+fn calculate_domain_paths<F, M>(machine: &Machine<F, M>, area: &Area, spill: &Spill) -> Result<(), String>
+where
+    F: FractalMath<M>,
+    M: MemType<M>,
+{
+    let domain = calculate_domain(machine, area);
+    let origin = |i: usize| domain.recorded[i].then(|| domain.origin(i).into());
+    calculate_paths(machine, area, origin, spill)?;
+    if machine.resolution_multiplier != Single {
+        let plank = area.plank();
+        let wrap = |i: usize| {
+            if !domain.is_on_mandelbrot_horizon(i) {
+                return Vec::new();
+            }
+            let (origin_re, origin_im) = domain.origin(i);
+            machine
+                .data_image
+                .wrap(origin_re, origin_im, machine.resolution_multiplier, plank)
+        };
+        calculate_paths(machine, area, wrap, spill)?;
+    }
+    spill.flush()
+}
+
+/**
+ * Values of the tile, from the path elements in its spill file
+ */
+fn accumulate_tile(tile: &Tile, spill_path: &str) -> Result<Vec<u64>, String> {
+    let file = File::open(spill_path).map_err(|e| format!("{}: {}", spill_path, e))?;
+    let mut reader = BufReader::new(file);
+    let mut values = vec![0u64; tile.width() * tile.height()];
+    let mut bytes = [0u8; 4];
+    loop {
+        match reader.read_exact(&mut bytes) {
+            Ok(()) => values[u32::from_le_bytes(bytes) as usize] += 1,
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => return Ok(values),
+            Err(e) => return Err(format!("{}: {}", spill_path, e)),
+        }
+    }
+}
+
+/**
+ * Values of the tile, by rows, as little endian u64
+ */
+fn save_tile(values: &[u64], path: &str) -> Result<(), String> {
+    let file = File::create(path).map_err(|e| format!("{}: {}", path, e))?;
+    let mut writer = BufWriter::new(file);
+    for value in values {
+        writer
+            .write_all(&value.to_le_bytes())
+            .map_err(|e| format!("{}: {}", path, e))?;
+    }
+    writer.flush().map_err(|e| format!("{}: {}", path, e))
+}
+
+fn read_tile(path: &str) -> Result<Vec<u64>, String> {
+    let bytes = fs::read(path).map_err(|e| format!("{}: {}", path, e))?;
+    Ok(bytes
+        .chunks_exact(8)
+        .map(|b| u64::from_le_bytes(b.try_into().unwrap()))
+        .collect())
+}
+
+/**
+ * Perfect coloring of all stitched pixels, same as perfectly_color_nebula_values
+ * Pixels are ordered by value, pixels of the same value by rows of the final image.
+ */
+struct PerfectRanks {
+    // rank of the next pixel of each value
+    next_rank: HashMap<u64, u64>,
+    lowest_color_pixels: u64,
+    single_color_use: u64,
+}
+
+fn perfect_ranks(values: &HashMap<u64, u64>, palette_color_count: u64) -> PerfectRanks {
+    let mut sorted: Vec<(u64, u64)> = values.iter().map(|(v, c)| (*v, *c)).collect();
+    sorted.sort();
+    let all_pixels_total: u64 = sorted.iter().map(|(_, c)| c).sum();
+    let zero_value_elements: u64 = sorted
+        .iter()
+        .filter(|(v, _)| *v <= COLORING_THRESHOLD)
+        .map(|(_, c)| c)
+        .sum();
+    let all_pixels_non_zero = all_pixels_total - zero_value_elements;
+    let single_color_use = all_pixels_non_zero / palette_color_count;
+    let left = all_pixels_non_zero - palette_color_count * single_color_use;
+
+    let mut next_rank = HashMap::new();
+    let mut rank = 0;
+    for (value, count) in sorted {
+        next_rank.insert(value, rank);
+        rank += count;
+    }
+    PerfectRanks {
+        next_rank,
+        lowest_color_pixels: left + zero_value_elements,
+        single_color_use,
+    }
+}
+
+impl PerfectRanks {
+    /**
+     * Palette index of the next pixel of this value
+     */
+    fn color_index(&mut self, value: u64) -> usize {
+        let rank = self.next_rank.get_mut(&value).expect("value not counted");
+        let r = *rank;
+        *rank += 1;
+        if value <= COLORING_THRESHOLD || r < self.lowest_color_pixels {
+            0
+        } else {
+            ((r - self.lowest_color_pixels) / self.single_color_use) as usize
+        }
+    }
+}
+
+/**
+ * Final PNG streamed by rows, only one row of tiles is in memory
+ */
+fn stitch(
+    tiles: &[Tile],
+    tile_paths: &[String],
+    palette: &Palette,
+    path: &str,
+    metadata: &Metadata,
+) -> Result<(), String> {
+    println!("stitch() {} tiles", tiles.len());
+    let mut values: HashMap<u64, u64> = HashMap::new();
+    for tile_path in tile_paths {
+        for value in read_tile(tile_path)? {
+            *values.entry(value).or_insert(0) += 1;
+        }
+    }
+    let mut ranks = perfect_ranks(&values, palette.spectrum.len() as u64);
+
+    let file = File::create(path).map_err(|e| format!("{}: {}", path, e))?;
+    let mut encoder = png::Encoder::new(BufWriter::new(file), metadata.width_x as u32, metadata.height_y as u32);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    for (key, value) in metadata.entries() {
+        encoder
+            .add_text_chunk(key.to_string(), value)
+            .map_err(|e| format!("{}: {}", path, e))?;
+    }
+    let mut writer = encoder
+        .write_header()
+        .and_then(|w| w.into_stream_writer())
+        .map_err(|e| format!("{}: {}", path, e))?;
+
+    let rows = tiles.iter().map(|t| t.row).max().map_or(0, |r| r + 1);
+    for row in 0..rows {
+        let row_tiles: Vec<(&Tile, Vec<u64>)> = tiles
+            .iter()
+            .zip(tile_paths)
+            .filter(|(t, _)| t.row == row)
+            .map(|(t, p)| read_tile(p).map(|v| (t, v)))
+            .collect::<Result<_, _>>()?;
+        let height = row_tiles.first().map_or(0, |(t, _)| t.height());
+        for y in 0..height {
+            let mut line = Vec::with_capacity(metadata.width_x * 3);
+            for (tile, tile_values) in &row_tiles {
+                let width = tile.width();
+                for value in &tile_values[y * width..(y + 1) * width] {
+                    let color = palette.spectrum_value(ranks.color_index(*value));
+                    line.extend_from_slice(&color.0);
+                }
+            }
+            writer.write_all(&line).map_err(|e| format!("{}: {}", path, e))?;
+        }
+    }
+    writer.finish().map_err(|e| format!("{}: {}", path, e))
+}
+
+/**
+ * Calculate paths of the whole image, accumulate tiles one by one, then stitch the final image, returns its path
+ * Nebula static images only, the final image is always PNG.
+ */
+pub fn execute<F, M>(config: &FractalConfig, fractal: F) -> Result<String, String>
+where
+    F: FractalMath<M> + 'static,
+    M: MemType<M> + 'static,
+{
+    let Tiling::Tiles(side) = config.tiling else {
+        return Err("tiling is off".to_string());
+    };
+    if config.fractal_calc_type != StaticImageNebula {
+        return Err(format!("{:?} can't be tiled", config.fractal_calc_type));
+    }
+    let files: &OutputFiles = &config.output_files;
+    let area = area::init(config);
+    let tiles = layout(config.width_xl, config.height_yl, side);
+    if tiles.is_empty() {
+        return Err("no tiles".to_string());
+    }
+    let machine: Machine<F, M> = machine::init(&path_config(config), fractal)?;
+    let tile_path = |tile: &Tile, extension| {
+        files.path(config.name, 0, &format!("_tile_{}_{}", tile.column, tile.row), extension)
+    };
+    let spill_paths = tiles.iter().map(|t| tile_path(t, "spill")).collect::<Result<Vec<_>, _>>()?;
+    let spill = create_spill(&tiles, side, spill_paths)?;
+    calculate_domain_paths(&machine, &area, &spill)?;
+
+    let mut tile_paths = Vec::new();
+    for (i, (tile, spill_path)) in tiles.iter().zip(&spill.paths).enumerate() {
+        println!("tile {} of {}", i + 1, tiles.len());
+        let values = accumulate_tile(tile, spill_path)?;
+        fs::remove_file(spill_path).map_err(|e| format!("{}: {}", spill_path, e))?;
+        let path = tile_path(tile, "raw")?;
+        save_tile(&values, &path)?;
+        tile_paths.push(path);
+    }
+
+    // geometry of the whole image
+    let mut metadata = machine.metadata(0);
+    metadata.width_x = config.width_xl;
+    metadata.height_y = config.height_yl;
+    metadata.center_re = config.center_re;
+    metadata.center_im = config.center_im;
+    metadata.width_re = config.width_re;
+
+    let path = files.path(config.name, 0, "", "png")?;
+    stitch(&tiles, &tile_paths, &machine.palette, &path, &metadata)?;
+    for tile_path in tile_paths {
+        fs::remove_file(&tile_path).map_err(|e| format!("{}: {}", tile_path, e))?;
+    }
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::NebulaImage;
+    use crate::files::OutputFiles;
+    use crate::files::OutputFormat::Png;
+    use crate::fractal::OrbitType::Finite;
+    use crate::fractal::FractalMath;
+    use crate::mem::Mem;
+    use crate::palettes::PaletteName::LinearBlue;
+    use crate::resolution_multiplier::ResolutionMultiplier::Square3;
+    use crate::tiles::{accumulate_tile, create_spill, execute, layout, perfect_ranks, Tiling};
+    use crate::fractal;
+    use std::collections::HashMap;
+
+    struct Nebula {}
+
+    impl FractalMath<Mem> for Nebula {
+        fn math(&self, m: &mut Mem, origin_re: f64, origin_im: f64) {
+            m.square();
+            m.plus(origin_re, origin_im);
+        }
+    }
+
+    #[test]
+    fn test_layout() {
+        let tiles = layout(10, 5, 4);
+
+        assert_eq!(tiles.len(), 6);
+        assert_eq!(tiles[1].x, (4, 8));
+        assert_eq!(tiles[2].x, (8, 10));
+        assert_eq!(tiles[2].y, (0, 4));
+        assert_eq!((tiles[5].column, tiles[5].row), (2, 1));
+        assert_eq!(tiles[5].y, (4, 5));
+        assert_eq!(tiles.iter().map(|t| t.width() * t.height()).sum::<usize>(), 50);
+    }
+
+    #[test]
+    fn test_spill() {
+        let tiles = layout(10, 5, 4);
+        let paths: Vec<String> = (0..tiles.len()).map(|i| format!("test_spill_{}.spill", i)).collect();
+        let spill = create_spill(&tiles, 4, paths.clone()).unwrap();
+
+        let (ti, pi) = spill.locate(9, 4);
+        assert_eq!((ti, pi), (5, 1));
+        spill.append(ti, &[pi, pi]).unwrap();
+        let (ti, pi) = spill.locate(5, 2);
+        assert_eq!((ti, pi), (1, 9));
+        spill.append(ti, &[pi]).unwrap();
+        spill.flush().unwrap();
+
+        let last = accumulate_tile(&tiles[5], &paths[5]).unwrap();
+        let second = accumulate_tile(&tiles[1], &paths[1]).unwrap();
+        let first = accumulate_tile(&tiles[0], &paths[0]).unwrap();
+        for path in paths {
+            std::fs::remove_file(path).unwrap();
+        }
+
+        assert_eq!(last, vec![0, 2]);
+        assert_eq!(second.iter().sum::<u64>(), 1);
+        assert_eq!(second[9], 1);
+        assert!(first.iter().all(|v| *v == 0));
+    }
+
+    #[test]
+    fn test_perfect_ranks() {
+        // 2 zero value pixels, 5 significant pixels, 2 palette colors
+        let values = HashMap::from([(0, 2), (10, 3), (20, 2)]);
+        let mut ranks = perfect_ranks(&values, 2);
+
+        let indexes: Vec<usize> = [0, 10, 10, 10, 20, 0, 20].iter().map(|v| ranks.color_index(*v)).collect();
+
+        // one left pixel colored by the lowest color
+        assert_eq!(indexes, vec![0, 0, 0, 0, 1, 0, 1]);
+    }
+
+    #[test]
+    fn test_execute() {
+        let mut c = NebulaImage {
+            name: "Tiles",
+            iteration_min: 5,
+            iteration_max: 200,
+            resolution_multiplier: Square3,
+            palette: LinearBlue,
+            width_x: 40,
+            height_y: 30,
+            width_re: 3.5,
+            center_re: -0.5,
+            center_im: 0.0,
+            orbits: Finite,
+        }
+        .init();
+        c.output_files = OutputFiles { dir: "test_output_tiles", template: "{name}", format: Png };
+
+        c.tiling = Tiling::Tiles(100);
+        let whole = image::open(execute::<Nebula, Mem>(&c, Nebula {}).unwrap()).unwrap();
+        c.tiling = Tiling::Tiles(16);
+        let tiled = image::open(execute::<Nebula, Mem>(&c, Nebula {}).unwrap()).unwrap();
+        let files = std::fs::read_dir("test_output_tiles").unwrap().count();
+        std::fs::remove_dir_all("test_output_tiles").unwrap();
+
+        // tile files are removed after stitching
+        assert_eq!(files, 1);
+        assert_eq!((tiled.width(), tiled.height()), (40, 30));
+        assert!(whole.as_bytes().iter().any(|b| *b > 0));
+        assert_eq!(whole.as_bytes(), tiled.as_bytes());
+    }
+
+    #[test]
+    fn test_execute_not_nebula() {
+        let mut c = fractal::init_trivial_static_config();
+        c.tiling = Tiling::Tiles(10);
+
+        assert!(execute::<Nebula, Mem>(&c, Nebula {}).is_err());
+    }
+}